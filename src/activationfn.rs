@@ -1,6 +1,42 @@
+use crate::Float;
 use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
 
-/// Activation function for a neuron.
+#[cfg(feature = "f32")]
+use std::f32::consts::PI;
+#[cfg(not(feature = "f32"))]
+use std::f64::consts::PI;
+
+/// A user-defined activation function, set on a node with
+/// `Node::set_activation_closure`.
+#[derive(Clone)]
+pub(crate) struct CustomActivation(Arc<dyn Fn(Float) -> Float + Send + Sync>);
+
+impl CustomActivation {
+    pub(crate) fn new(activation_fn: impl Fn(Float) -> Float + Send + Sync + 'static) -> Self {
+        Self(Arc::new(activation_fn))
+    }
+
+    pub(crate) fn run(&self, x: Float) -> Float {
+        (self.0)(x)
+    }
+
+    /// The derivative at `x`, by central difference, with the step that
+    /// balances rounding and truncation error.
+    pub(crate) fn derivative(&self, x: Float) -> Float {
+        let h = Float::EPSILON.cbrt() * x.abs().max(1.0);
+        (self.run(x + h) - self.run(x - h)) / (2.0 * h)
+    }
+}
+
+impl fmt::Debug for CustomActivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomActivation")
+    }
+}
+
+/// Activation function for a neuron. For a function that is not listed here,
+/// see `Node::set_activation_closure`.
 #[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
 pub enum ActivationFn {
     /// `|x| x.max(0.0)`
@@ -16,11 +52,22 @@ pub enum ActivationFn {
     Linear,
 
     /// `|x, threshold| if x > threshold { 1.0 } else { 0.0 }`
-    Step(f64),
+    Step(Float),
+
+    /// `|x| 0.5 * x * (1.0 + (sqrt(2.0 / PI) * (x + 0.044715 * x.powi(3))).tanh())`,
+    /// the tanh approximation of the Gaussian Error Linear Unit
+    GELU,
+
+    /// `|x, k| 1.0 / (1.0 + (-k * x).exp())`, a sigmoid with steepness `k`.
+    /// A steepness of 1 is the same as `Sigmoid`.
+    ParametricSigmoid(Float),
+
+    /// `|x, a| a * x.tanh()`, a tanh with amplitude `a`.
+    ScaledTanh(Float),
 }
 
 impl ActivationFn {
-    pub(crate) fn run(&self, x: f64) -> f64 {
+    pub(crate) fn run(&self, x: Float) -> Float {
         match self {
             ActivationFn::ReLU => x.max(0.0),
             ActivationFn::Sigmoid => 1.0 / (1.0 + (-x).exp()),
@@ -33,6 +80,69 @@ impl ActivationFn {
                     0.0
                 }
             }
+            ActivationFn::GELU => {
+                0.5 * x * (1.0 + ((2.0 / PI).sqrt() * (x + 0.044715 * x.powi(3))).tanh())
+            }
+            ActivationFn::ParametricSigmoid(steepness) => 1.0 / (1.0 + (-steepness * x).exp()),
+            ActivationFn::ScaledTanh(amplitude) => amplitude * x.tanh(),
+        }
+    }
+
+    /// The lower and upper bounds of the function's output, where `None`
+    /// means unbounded. The bounds may be reached only in the limit, as with
+    /// `Sigmoid`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::activationfn::ActivationFn;
+    /// assert_eq!(ActivationFn::Sigmoid.output_range(), (Some(0.0), Some(1.0)));
+    /// assert_eq!(ActivationFn::ReLU.output_range(), (Some(0.0), None));
+    /// assert_eq!(ActivationFn::Linear.output_range(), (None, None));
+    /// ```
+    pub fn output_range(&self) -> (Option<Float>, Option<Float>) {
+        match self {
+            ActivationFn::ReLU => (Some(0.0), None),
+            ActivationFn::Sigmoid => (Some(0.0), Some(1.0)),
+            ActivationFn::Tanh => (Some(-1.0), Some(1.0)),
+            ActivationFn::Linear => (None, None),
+            ActivationFn::Step(_) => (Some(0.0), Some(1.0)),
+            // the minimum is about -0.17004 near x = -0.752, rounded down
+            ActivationFn::GELU => (Some(-0.170041), None),
+            ActivationFn::ParametricSigmoid(_) => (Some(0.0), Some(1.0)),
+            ActivationFn::ScaledTanh(amplitude) => (Some(-amplitude.abs()), Some(amplitude.abs())),
+        }
+    }
+
+    /// The derivative at `x`. Step functions have a derivative of 0
+    /// everywhere, and ReLU has a derivative of 0 at 0.
+    pub(crate) fn derivative(&self, x: Float) -> Float {
+        match self {
+            ActivationFn::ReLU => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ActivationFn::Sigmoid => {
+                let sigmoid = self.run(x);
+                sigmoid * (1.0 - sigmoid)
+            }
+            ActivationFn::Tanh => 1.0 - x.tanh().powi(2),
+            ActivationFn::Linear => 1.0,
+            ActivationFn::Step(_) => 0.0,
+            ActivationFn::GELU => {
+                let c = (2.0 / PI).sqrt();
+                let tanh = (c * (x + 0.044715 * x.powi(3))).tanh();
+
+                0.5 * (1.0 + tanh)
+                    + 0.5 * x * (1.0 - tanh.powi(2)) * c * (1.0 + 3.0 * 0.044715 * x.powi(2))
+            }
+            ActivationFn::ParametricSigmoid(steepness) => {
+                let sigmoid = self.run(x);
+                steepness * sigmoid * (1.0 - sigmoid)
+            }
+            ActivationFn::ScaledTanh(amplitude) => amplitude * (1.0 - x.tanh().powi(2)),
         }
     }
 }
@@ -0,0 +1,79 @@
+use crate::{
+    activationfn::ActivationFn,
+    layer::{LayerID, LayerSpec},
+    network::Network,
+    weightinit::WeightInit,
+};
+use anyhow::{Context, Result};
+
+/// Builds a fully-connected feedforward network one layer at a time.
+///
+/// ### Example
+/// ```
+/// # use nnrs::{builder::NetworkBuilder, activationfn::ActivationFn};
+/// let mut network = NetworkBuilder::new()
+///     .inputs(3)
+///     .dense(8, ActivationFn::ReLU)
+///     .dense(4, ActivationFn::ReLU)
+///     .outputs(2, ActivationFn::Sigmoid)
+///     .build()
+///     .unwrap();
+///
+/// let mut output = vec![];
+/// network.fire(vec![0.0, 0.5, 1.0], &mut output).unwrap();
+/// assert_eq!(output.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NetworkBuilder {
+    inputs: usize,
+    hidden: Vec<LayerSpec>,
+    outputs: Option<(usize, ActivationFn)>,
+}
+
+impl NetworkBuilder {
+    /// Starts a network with no inputs, hidden layers or outputs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of input nodes.
+    pub fn inputs(mut self, count: usize) -> Self {
+        self.inputs = count;
+        self
+    }
+
+    /// Adds a hidden layer of `size` nodes after the previous one, connected
+    /// to every node of the previous layer.
+    pub fn dense(mut self, size: usize, activation: ActivationFn) -> Self {
+        self.hidden.push(LayerSpec { size, activation });
+        self
+    }
+
+    /// Sets the number of output nodes and their activation function.
+    pub fn outputs(mut self, count: usize, activation: ActivationFn) -> Self {
+        self.outputs = Some((count, activation));
+        self
+    }
+
+    /// Creates the network. Like `Network::feedforward`, every node is
+    /// connected to every node in the next layer with a random weight between
+    /// -1 and 1. Fails if `outputs` was not called.
+    pub fn build(self) -> Result<Network> {
+        let (output_ct, output_activation) = self
+            .outputs
+            .context("A network needs outputs; call `outputs` before `build`")?;
+
+        let mut network = Network::create(self.inputs, output_ct, output_activation)?;
+        network.set_layer_activation(LayerID::OutputLayer, output_activation)?;
+
+        let mut last_layer = LayerID::InputLayer;
+
+        for spec in self.hidden {
+            last_layer = network.push_dense(spec)?;
+        }
+
+        network.connect_layers(last_layer, LayerID::OutputLayer, WeightInit::Uniform(1.0))?;
+
+        Ok(network)
+    }
+}
@@ -0,0 +1,173 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{layer::LayerID, network::Network, node::NodeType};
+use anyhow::{ensure, Context, Result};
+
+/// The work done when one layer fires: the edges leaving the layer, as
+/// `(node_from, node_to, edge)` indexes into `Network::nodes` and
+/// `Network::edges`, and the nodes of the next layer to activate afterwards,
+/// in order of id. `lateral[i]` holds the `(node_to, edge)` lateral edges
+/// leaving `activate[i]`, applied as soon as it has activated.
+#[derive(Clone)]
+pub(crate) struct LayerPlan {
+    pub(crate) edges: Vec<(usize, usize, usize)>,
+    pub(crate) activate: Vec<usize>,
+    pub(crate) lateral: Vec<Vec<(usize, usize)>>,
+}
+
+/// Precomputed firing order of a network, so that firing indexes directly
+/// into the node and edge vecs instead of searching them by id. Only the
+/// structure, including which edges are enabled, is cached; weights, biases
+/// and activation functions are read from the network on every fire.
+#[derive(Clone)]
+pub(crate) struct FireCache {
+    pub(crate) indexes: HashMap<usize, usize>,
+    pub(crate) input_indexes: Vec<usize>,
+    pub(crate) output_indexes: Vec<usize>,
+    pub(crate) layers: Vec<LayerPlan>,
+    /// The ids of the output nodes that no input node can reach, for strict
+    /// mode.
+    pub(crate) disconnected_outputs: Vec<usize>,
+}
+
+impl FireCache {
+    pub(crate) fn new(network: &Network) -> Result<Self> {
+        let indexes = network
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect::<HashMap<usize, usize>>();
+
+        for node in network.nodes.iter() {
+            ensure!(
+                network.layers.contains(&node.layer_id),
+                "Node {} is in layer {:?}, which does not exist",
+                node.id,
+                node.layer_id
+            );
+        }
+
+        let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        for (edge_index, edge) in network.edges.iter().enumerate() {
+            let node_from_index = *indexes.get(&edge.node_from_id).with_context(|| {
+                format!(
+                    "Edge {} comes from node {}, which does not exist",
+                    edge.id, edge.node_from_id
+                )
+            })?;
+
+            ensure!(
+                indexes.contains_key(&edge.node_to_id),
+                "Edge {} from node {} in layer {:?} goes to node {}, which does not exist",
+                edge.id,
+                edge.node_from_id,
+                network.nodes[node_from_index].layer_id,
+                edge.node_to_id
+            );
+
+            if !edge.recurrent && !edge.lateral {
+                outgoing
+                    .entry(edge.node_from_id)
+                    .or_default()
+                    .push(edge_index);
+            }
+        }
+
+        let mut lateral: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        for (edge_index, edge) in network.edges.iter().enumerate() {
+            if edge.lateral {
+                lateral
+                    .entry(edge.node_from_id)
+                    .or_default()
+                    .push(edge_index);
+            }
+        }
+
+        let layer_indexes = |layer_id: LayerID| {
+            let mut layer_indexes = network
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.layer_id == layer_id)
+                .map(|(index, _)| index)
+                .collect::<Vec<usize>>();
+            layer_indexes.sort_by_key(|index| network.nodes[*index].id);
+            layer_indexes
+        };
+
+        let lateral_edges = |node_index: usize| {
+            lateral
+                .get(&network.nodes[node_index].id)
+                .into_iter()
+                .flatten()
+                .map(|edge_index| (indexes[&network.edges[*edge_index].node_to_id], *edge_index))
+                .collect::<Vec<(usize, usize)>>()
+        };
+
+        let mut layer_ids = network.layers.clone();
+        layer_ids.sort();
+
+        let mut layers = Vec::with_capacity(layer_ids.len());
+
+        for layer_id in layer_ids.iter() {
+            let mut edges = Vec::new();
+
+            for node_index in layer_indexes(*layer_id) {
+                let node_id = network.nodes[node_index].id;
+
+                for edge_index in outgoing.get(&node_id).into_iter().flatten() {
+                    let node_to_index = indexes[&network.edges[*edge_index].node_to_id];
+
+                    edges.push((node_index, node_to_index, *edge_index));
+                }
+            }
+
+            // the output layer has no next layer, and its nodes were already
+            // activated when the layer before it fired
+            let next_layer_indexes =
+                match layer_ids.iter().filter(|layer| **layer > *layer_id).min() {
+                    Some(next_layer) => layer_indexes(*next_layer),
+                    None => vec![],
+                };
+
+            let (bias_indexes, activate): (Vec<usize>, Vec<usize>) = next_layer_indexes
+                .into_iter()
+                .partition(|index| network.nodes[*index].node_type == NodeType::BiasNode);
+
+            // bias nodes always hold 1, so their lateral edges can be applied
+            // before anything in the layer activates
+            for node_index in bias_indexes {
+                for (node_to_index, edge_index) in lateral_edges(node_index) {
+                    edges.push((node_index, node_to_index, edge_index));
+                }
+            }
+
+            let lateral = activate.iter().map(|index| lateral_edges(*index)).collect();
+
+            layers.push(LayerPlan {
+                edges,
+                activate,
+                lateral,
+            });
+        }
+
+        let sorted_indexes = |ids: Vec<usize>| ids.iter().map(|id| indexes[id]).collect();
+
+        Ok(Self {
+            input_indexes: sorted_indexes(network.input_node_ids()),
+            output_indexes: sorted_indexes(network.output_node_ids()),
+            indexes,
+            layers,
+            disconnected_outputs: network.disconnected_output_ids(),
+        })
+    }
+}
+
+impl fmt::Debug for FireCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FireCache").finish_non_exhaustive()
+    }
+}
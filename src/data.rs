@@ -0,0 +1,128 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+};
+
+use crate::Float;
+use anyhow::{bail, ensure, Context, Result};
+
+/// Input vectors and their target vectors, one of each per sample.
+pub type Dataset = (Vec<Vec<Float>>, Vec<Vec<Float>>);
+
+/// Loads a dataset from a CSV file. The first `input_cols` columns of each
+/// row become an input vector and the remaining columns a target vector.
+/// Cells must be numbers; a first row in which no cell is a number is
+/// treated as a header and skipped, while a first row with only some
+/// non-numeric cells is an error like any other row. Empty lines are
+/// ignored, and every row must have the same number of columns.
+///
+/// ### Example
+/// ```
+/// # use nnrs::data::{load_csv, save_csv};
+/// save_csv("xor.csv", &[vec![0.0, 1.0]], &[vec![1.0]]).unwrap();
+///
+/// let (inputs, targets) = load_csv("xor.csv", 2).unwrap();
+/// assert_eq!(inputs, vec![vec![0.0, 1.0]]);
+/// assert_eq!(targets, vec![vec![1.0]]);
+/// # std::fs::remove_file("xor.csv").unwrap();
+/// ```
+pub fn load_csv(path: &str, input_cols: usize) -> Result<Dataset> {
+    let mut file = File::open(path).context("Could not open file")?;
+    let mut string = String::new();
+    file.read_to_string(&mut string)
+        .context("Could not read file")?;
+
+    parse_csv(&string, input_cols)
+}
+
+/// Saves a dataset to a CSV file, one row per sample with the inputs
+/// followed by the targets. No header is written.
+pub fn save_csv(path: &str, inputs: &[Vec<Float>], targets: &[Vec<Float>]) -> Result<()> {
+    ensure!(
+        inputs.len() == targets.len(),
+        "Number of inputs does not match number of targets"
+    );
+
+    let mut string = String::new();
+
+    for (row, (input, target)) in inputs.iter().zip(targets).enumerate() {
+        ensure!(
+            input.len() == inputs[0].len() && target.len() == targets[0].len(),
+            "Row {} has a different number of columns than the first row",
+            row + 1
+        );
+
+        let cells = input
+            .iter()
+            .chain(target)
+            .map(|value| value.to_string())
+            .collect::<Vec<String>>();
+
+        string.push_str(&cells.join(","));
+        string.push('\n');
+    }
+
+    let mut file = File::create(path).context("Could not create file")?;
+    file.write_all(string.as_bytes())
+        .context("Could not write to file")?;
+    Ok(())
+}
+
+fn parse_csv(string: &str, input_cols: usize) -> Result<Dataset> {
+    let mut inputs = Vec::new();
+    let mut targets = Vec::new();
+    let mut columns = None;
+
+    for (line_index, line) in string.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let cells = line.split(',').map(str::trim).collect::<Vec<&str>>();
+        let parsed = cells
+            .iter()
+            .map(|cell| cell.parse::<Float>())
+            .collect::<Vec<_>>();
+
+        if columns.is_none() && line_index == 0 && parsed.iter().all(|cell| cell.is_err()) {
+            // a header row; a partly numeric first row is more likely a
+            // mistyped sample, which should not be dropped silently
+            columns = Some(cells.len());
+            continue;
+        }
+
+        let expected = *columns.get_or_insert(cells.len());
+        ensure!(
+            cells.len() == expected,
+            "Row {} has {} columns, expected {}",
+            line_index + 1,
+            cells.len(),
+            expected
+        );
+        ensure!(
+            input_cols <= expected,
+            "Cannot take {} input columns from rows with {} columns",
+            input_cols,
+            expected
+        );
+
+        let mut row = Vec::with_capacity(cells.len());
+
+        for (column, (cell, value)) in cells.iter().zip(parsed).enumerate() {
+            match value {
+                Ok(value) => row.push(value),
+                Err(_) => bail!(
+                    "Row {}, column {}: {:?} is not a number",
+                    line_index + 1,
+                    column + 1,
+                    cell
+                ),
+            }
+        }
+
+        targets.push(row.split_off(input_cols));
+        inputs.push(row);
+    }
+
+    Ok((inputs, targets))
+}
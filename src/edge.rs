@@ -1,4 +1,4 @@
-use crate::network::Network;
+use crate::{network::Network, node::NodeType, Float};
 use anyhow::{ensure, Context, Result};
 use serde::{Deserialize, Serialize};
 
@@ -6,13 +6,26 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Edge {
     pub(crate) id: usize,
-    pub(crate) weight: f64,
+    pub(crate) weight: Float,
     pub(crate) node_from_id: usize,
     pub(crate) node_to_id: usize,
+    #[serde(default)]
+    pub(crate) recurrent: bool,
+    #[serde(default)]
+    pub(crate) lateral: bool,
+    #[serde(default)]
+    pub(crate) frozen: bool,
+    #[serde(default = "enabled_by_default")]
+    pub(crate) enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
 }
 
 impl Edge {
-    /// Creates a new edge.
+    /// Creates a new edge, returning its id. Like node ids, edge ids are
+    /// issued in increasing order and never reused.
     ///
     /// ### Examples
     /// ```
@@ -22,18 +35,18 @@ impl Edge {
     /// # let input_node_id = network.input_node_ids().pop().unwrap();
     /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
     /// # let output_node_id = network.output_node_ids().pop().unwrap();
-    /// Edge::create(&mut network, input_node_id, hidden_node_id, 1.3);
-    /// Edge::create(&mut network, hidden_node_id, output_node_id, 1.5);
-    /// Edge::create(&mut network, hidden_node_id, output_node_id, 2.0);
+    /// Edge::create(&mut network, input_node_id, hidden_node_id, 1.3).unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 1.5).unwrap();
+    ///
+    /// // the two nodes are already connected
+    /// assert!(Edge::create(&mut network, hidden_node_id, output_node_id, 2.0).is_err());
     /// ```
     pub fn create(
         network: &mut Network,
         node_from_id: usize,
         node_to_id: usize,
-        weight: f64,
+        weight: Float,
     ) -> Result<usize> {
-        let id = network.edges.iter().map(|e| e.id).max().unwrap_or(0) + 1;
-
         let node_from = network
             .get_node(node_from_id)
             .context("Node from does not exist")?;
@@ -47,21 +60,187 @@ impl Edge {
             "node_to must be in a layer after node_from"
         );
 
+        Self::insert(network, node_from_id, node_to_id, weight, false, false)
+    }
+
+    /// Creates a new recurrent edge. Recurrent edges may point to any node,
+    /// including nodes in earlier layers, and carry the value of `node_from`
+    /// from the previous time step. They are ignored by `Network::fire` and
+    /// only used by `Network::fire_sequence`.
+    ///
+    /// ### Examples
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, layer::LayerID, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// # let layerid = network.add_layer();
+    /// # let input_node_id = network.input_node_ids().pop().unwrap();
+    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
+    /// # let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, hidden_node_id, 1.3).unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 1.5).unwrap();
+    /// Edge::create_recurrent(&mut network, output_node_id, hidden_node_id, 0.5).unwrap();
+    /// ```
+    pub fn create_recurrent(
+        network: &mut Network,
+        node_from_id: usize,
+        node_to_id: usize,
+        weight: Float,
+    ) -> Result<usize> {
+        Self::insert(network, node_from_id, node_to_id, weight, true, false)
+    }
+
+    /// Creates a new lateral edge, between two nodes in the same layer.
+    /// `node_from` must have a lower id than `node_to`, and `node_to` cannot
+    /// be an input node.
+    ///
+    /// Once all edges from earlier layers have arrived, the nodes of a layer
+    /// activate one at a time in order of id. After each node activates, its
+    /// lateral edges add its value to the nodes with higher ids. So every
+    /// lateral edge sees the final value of its source node.
+    ///
+    /// ### Examples
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let layerid = network.add_layer();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let first_id = Node::create(&mut network, layerid, 0.0).unwrap();
+    /// let second_id = Node::create(&mut network, layerid, 0.0).unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    ///
+    /// Edge::create(&mut network, input_node_id, first_id, 1.0).unwrap();
+    /// Edge::create_lateral(&mut network, first_id, second_id, 2.0).unwrap();
+    /// Edge::create(&mut network, second_id, output_node_id, 1.0).unwrap();
+    ///
+    /// // lateral edges only point towards higher ids
+    /// assert!(Edge::create_lateral(&mut network, second_id, first_id, 2.0).is_err());
+    ///
+    /// let mut output = vec![];
+    /// network.fire(vec![0.5], &mut output).unwrap();
+    /// assert_eq!(output, vec![1.0]);
+    /// ```
+    pub fn create_lateral(
+        network: &mut Network,
+        node_from_id: usize,
+        node_to_id: usize,
+        weight: Float,
+    ) -> Result<usize> {
+        let node_from = network
+            .get_node(node_from_id)
+            .context("Node from does not exist")?;
+
+        let node_to = network
+            .get_node(node_to_id)
+            .context("Node to does not exist")?;
+
+        ensure!(
+            node_to.layer_id == node_from.layer_id,
+            "node_to must be in the same layer as node_from"
+        );
+
+        ensure!(
+            node_from_id < node_to_id,
+            "node_from must have a lower id than node_to"
+        );
+
+        ensure!(
+            node_to.node_type != NodeType::InputNode,
+            "Node {} is an input node and cannot have lateral edges",
+            node_to_id
+        );
+
+        Self::insert(network, node_from_id, node_to_id, weight, false, true)
+    }
+
+    fn insert(
+        network: &mut Network,
+        node_from_id: usize,
+        node_to_id: usize,
+        weight: Float,
+        recurrent: bool,
+        lateral: bool,
+    ) -> Result<usize> {
+        let id = network.new_edge_id();
+
+        ensure!(
+            network.get_node(node_from_id).is_some(),
+            "Node from does not exist"
+        );
+
+        let node_to = network
+            .get_node(node_to_id)
+            .context("Node to does not exist")?;
+
+        ensure!(
+            node_to.node_type != NodeType::BiasNode,
+            "Node {} is a bias node and cannot have incoming edges",
+            node_to_id
+        );
+
         ensure!(
             network.get_edge(id).is_none(),
             "Edge with id {} already exists",
             id
         );
 
+        ensure!(
+            !network
+                .edges
+                .iter()
+                .any(|e| e.node_from_id == node_from_id && e.node_to_id == node_to_id),
+            "An edge from node {} to node {} already exists",
+            node_from_id,
+            node_to_id
+        );
+
         let edge = Edge {
             id,
             weight,
             node_from_id,
             node_to_id,
+            recurrent,
+            lateral,
+            frozen: false,
+            enabled: true,
         };
 
         network.edges.push(edge);
+        network.next_edge_id = id + 1;
+        network.invalidate_cache();
 
         Ok(id)
     }
+
+    /// Creates a new edge, or updates the weight of the edge that already
+    /// connects the two nodes.
+    ///
+    /// ### Examples
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// # let input_node_id = network.input_node_ids().pop().unwrap();
+    /// # let output_node_id = network.output_node_ids().pop().unwrap();
+    /// let edge_id = Edge::create(&mut network, input_node_id, output_node_id, 1.3).unwrap();
+    /// let same_id = Edge::create_or_update(&mut network, input_node_id, output_node_id, 2.0).unwrap();
+    ///
+    /// assert_eq!(edge_id, same_id);
+    /// ```
+    pub fn create_or_update(
+        network: &mut Network,
+        node_from_id: usize,
+        node_to_id: usize,
+        weight: Float,
+    ) -> Result<usize> {
+        match network
+            .edges
+            .iter_mut()
+            .find(|e| e.node_from_id == node_from_id && e.node_to_id == node_to_id)
+        {
+            Some(edge) => {
+                edge.weight = weight;
+                Ok(edge.id)
+            }
+            None => Self::create(network, node_from_id, node_to_id, weight),
+        }
+    }
 }
@@ -0,0 +1,97 @@
+use crate::{
+    network::{argmax, Network},
+    Float,
+};
+use anyhow::{ensure, Result};
+
+/// How an `Ensemble` combines the outputs of its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnsembleMode {
+    /// Each output is the mean of the members' outputs.
+    Average,
+
+    /// Each member votes for its largest output. Each output is the fraction
+    /// of members that voted for it, so the largest output is the majority.
+    MajorityVote,
+}
+
+/// A group of networks with the same inputs and outputs, fired together.
+#[derive(Debug, Clone)]
+pub struct Ensemble {
+    members: Vec<Network>,
+    mode: EnsembleMode,
+}
+
+impl Ensemble {
+    /// Creates an ensemble. All members must have the same number of inputs
+    /// and outputs, and there must be at least one member.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, ensemble::{Ensemble, EnsembleMode}, activationfn::ActivationFn};
+    /// let members = (0..3)
+    ///     .map(|_| Network::feedforward(&[2, 4, 1], ActivationFn::Sigmoid).unwrap())
+    ///     .collect();
+    ///
+    /// let mut ensemble = Ensemble::new(members, EnsembleMode::Average).unwrap();
+    /// let output = ensemble.fire(vec![0.0, 1.0]).unwrap();
+    /// assert_eq!(output.len(), 1);
+    /// ```
+    pub fn new(members: Vec<Network>, mode: EnsembleMode) -> Result<Self> {
+        ensure!(!members.is_empty(), "An ensemble needs at least one member");
+
+        let input_size = members[0].input_size();
+        let output_size = members[0].output_size();
+
+        for (index, member) in members.iter().enumerate() {
+            ensure!(
+                member.input_size() == input_size && member.output_size() == output_size,
+                "Member {} has {} inputs and {} outputs, expected {} and {}",
+                index,
+                member.input_size(),
+                member.output_size(),
+                input_size,
+                output_size
+            );
+        }
+
+        Ok(Self { members, mode })
+    }
+
+    /// The networks in the ensemble.
+    pub fn members(&self) -> &[Network] {
+        &self.members
+    }
+
+    /// Fires every member and combines their outputs according to the mode.
+    pub fn fire(&mut self, inputs: Vec<Float>) -> Result<Vec<Float>> {
+        let output_size = self.members[0].output_size();
+        let mut combined = vec![0.0; output_size];
+        let mut outputs = vec![0.0; output_size];
+
+        for member in self.members.iter_mut() {
+            member.fire_into(&inputs, &mut outputs)?;
+
+            match self.mode {
+                EnsembleMode::Average => {
+                    for (total, output) in combined.iter_mut().zip(outputs.iter()) {
+                        *total += output;
+                    }
+                }
+                EnsembleMode::MajorityVote => {
+                    if output_size > 0 {
+                        combined[argmax(&outputs)] += 1.0;
+                    }
+                }
+            }
+        }
+
+        let count = self.members.len() as Float;
+
+        for total in combined.iter_mut() {
+            *total /= count;
+        }
+
+        Ok(combined)
+    }
+}
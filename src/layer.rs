@@ -1,3 +1,4 @@
+use crate::activationfn::ActivationFn;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
@@ -15,7 +16,7 @@ pub enum LayerID {
 }
 
 impl LayerID {
-    /// Returns the next layer id.
+    /// Returns true if this is a hidden layer.
     pub fn is_hidden(&self) -> bool {
         matches!(self, LayerID::HiddenLayer(_))
     }
@@ -51,3 +52,13 @@ impl PartialOrd for LayerID {
         Some(self.cmp(other))
     }
 }
+
+/// Describes a fully-connected hidden layer, for use with `Network::push_dense`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerSpec {
+    /// The number of nodes in the layer.
+    pub size: usize,
+
+    /// The activation function of every node in the layer.
+    pub activation: ActivationFn,
+}
@@ -9,7 +9,7 @@
 //! use nnrs::{network::Network, node::Node, edge::Edge, layer::LayerID, activationfn::ActivationFn};
 //!
 //! let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
-//! let mut output: Vec<f64> = Vec::new();
+//! let mut output = Vec::new();
 //!
 //! let layer_id = network.add_layer();
 //!
@@ -29,6 +29,7 @@
 pub mod edge;
 
 /// Contains the `LayerID` enum. Layer IDs are used to group `Nodes`.
+/// Also contains `LayerSpec`, which describes a fully-connected layer.
 pub mod layer;
 
 /// Contains the `Network` struct. Use this to interact with your Network.
@@ -37,9 +38,39 @@ pub mod network;
 /// Nodes are the basic building blocks of a neural network.
 pub mod node;
 
+/// Contains the `WeightInit` enum, used to pick the weights of new edges.
+pub mod weightinit;
+
+/// Contains the `NetworkBuilder` struct, for building feedforward networks.
+pub mod builder;
+
+/// The type of node values, biases and weights. `f64` by default, or `f32`
+/// with the `f32` feature.
+#[cfg(not(feature = "f32"))]
+pub type Float = f64;
+
+/// The type of node values, biases and weights. `f64` by default, or `f32`
+/// with the `f32` feature.
+#[cfg(feature = "f32")]
+pub type Float = f32;
+
 /// Activation functions
 pub mod activationfn;
 
+/// Fixed-point inference
+pub mod quantized;
+
+/// Contains the `Ensemble` struct, for firing several networks together.
+pub mod ensemble;
+
+/// Loading and saving datasets as CSV
+pub mod data;
+
+/// Contains the `Normalizer` struct, for rescaling inputs and targets.
+pub mod normalizer;
+
+mod cache;
+
 // /// NEAT training for the Neural Network
 // #[cfg(feature = "neat")]
 // pub mod neat;
@@ -65,7 +96,7 @@ fn test_creation() -> anyhow::Result<crate::network::Network> {
 #[test]
 fn test_io() -> anyhow::Result<()> {
     let mut network = test_creation()?;
-    let mut output: Vec<f64> = Vec::new();
+    let mut output: Vec<Float> = Vec::new();
 
     network.fire(vec![0.8], &mut output)?;
 
@@ -94,6 +125,2742 @@ fn test_serialization() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_summary() -> anyhow::Result<()> {
+    use crate::{layer::LayerID, node::Node};
+
+    let mut network = test_creation()?;
+
+    assert_eq!(
+        network.summary(),
+        "Layers:\n  \
+           InputLayer: 1 nodes\n  \
+           HiddenLayer(0): 1 nodes\n  \
+           OutputLayer: 1 nodes\n\
+         Edges: 3\n\
+         Trainable parameters: 5\n\
+         FLOPs per fire: 5\n\
+         Activations:\n  \
+           Linear: 1\n  \
+           ReLU: 2\n"
+    );
+
+    let hidden_node_id = network.layer_node_ids(LayerID::HiddenLayer(0))?[0];
+    Node::set_activation_closure(&mut network, hidden_node_id, |x| x * x)?;
+
+    assert!(network
+        .summary()
+        .ends_with("Activations:\n  Custom: 1\n  Linear: 1\n  ReLU: 1\n"));
+
+    Ok(())
+}
+
+#[test]
+fn test_layer_activation() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerID, node::Node};
+
+    let mut network = test_creation()?;
+    let hidden_id = LayerID::HiddenLayer(0);
+
+    network.set_layer_activation(hidden_id, ActivationFn::Sigmoid)?;
+    let new_node_id = Node::create(&mut network, hidden_id, 0.0)?;
+
+    assert!(network
+        .nodes
+        .iter()
+        .filter(|node| node.layer_id == hidden_id)
+        .all(|node| node.activation_fn == ActivationFn::Sigmoid));
+    assert_eq!(
+        network.get_node(new_node_id).unwrap().activation_fn,
+        ActivationFn::Sigmoid
+    );
+
+    let output_node_id = Node::create(&mut network, LayerID::OutputLayer, 0.0)?;
+    assert_eq!(
+        network.get_node(output_node_id).unwrap().activation_fn,
+        ActivationFn::ReLU
+    );
+
+    assert!(network
+        .set_layer_activation(LayerID::HiddenLayer(5), ActivationFn::Linear)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_duplicate_edges() -> anyhow::Result<()> {
+    use crate::edge::Edge;
+
+    let mut network = test_creation()?;
+
+    let input_node_id = network.input_node_ids().pop().unwrap();
+    let output_node_id = network.output_node_ids().pop().unwrap();
+
+    assert!(Edge::create(&mut network, input_node_id, output_node_id, 1.0).is_err());
+    assert_eq!(network.edges.len(), 3);
+
+    let edge_id = Edge::create_or_update(&mut network, input_node_id, output_node_id, 1.0)?;
+    assert_eq!(network.edges.len(), 3);
+    assert_eq!(network.get_edge(edge_id).unwrap().weight, 1.0);
+
+    Ok(())
+}
+
+#[test]
+fn test_split_edge() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network};
+
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let input_node_id = network.input_node_ids().pop().unwrap();
+    let output_node_id = network.output_node_ids().pop().unwrap();
+    let edge_id = Edge::create(&mut network, input_node_id, output_node_id, 1.5)?;
+
+    let mut output = vec![];
+    network.fire(vec![0.8], &mut output)?;
+    let expected = output.clone();
+
+    // input -> output has no layer in between, so one is created
+    let node_id = network.split_edge(edge_id)?;
+    assert_eq!(
+        network.get_node(node_id).unwrap().layer_id,
+        LayerID::HiddenLayer(0)
+    );
+    assert!(!network.get_edge(edge_id).unwrap().enabled);
+    assert_eq!(network.edges.len(), 3);
+
+    // a disabled edge stays off
+    assert!(network.split_edge(edge_id).is_err());
+
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, expected);
+
+    // hidden -> output with no later hidden layer also needs a new layer
+    let edge_id = network
+        .edges
+        .iter()
+        .find(|edge| edge.node_from_id == node_id)
+        .unwrap()
+        .id;
+    let second_node_id = network.split_edge(edge_id)?;
+    assert_eq!(
+        network.get_node(second_node_id).unwrap().layer_id,
+        LayerID::HiddenLayer(1)
+    );
+
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, expected);
+
+    // the halves of a frozen edge are frozen
+    let edge_id = network
+        .edges
+        .iter()
+        .find(|edge| edge.node_to_id == output_node_id && edge.enabled)
+        .unwrap()
+        .id;
+    network
+        .edges
+        .iter_mut()
+        .find(|edge| edge.id == edge_id)
+        .unwrap()
+        .frozen = true;
+    let third_node_id = network.split_edge(edge_id)?;
+    assert!(network.get_edge(edge_id).unwrap().frozen);
+    assert_eq!(
+        network
+            .edges
+            .iter()
+            .filter(|edge| edge.frozen && edge.enabled)
+            .map(|edge| (edge.node_from_id, edge.node_to_id))
+            .filter(|(from, to)| *from == third_node_id || *to == third_node_id)
+            .count(),
+        2
+    );
+
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, expected);
+
+    // recurrent edges point backwards and cannot be split; the failed split
+    // leaves the network as it was
+    let recurrent_id = Edge::create_recurrent(&mut network, second_node_id, node_id, 0.5)?;
+    let before = network.clone();
+
+    let error = network.split_edge(recurrent_id).unwrap_err();
+    assert!(error.to_string().contains("recurrent"));
+    assert!(network.diff(&before).is_empty());
+    assert_eq!(network.layers, before.layers);
+    assert_eq!(network.summary(), before.summary());
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_into() -> anyhow::Result<()> {
+    let mut network = test_creation()?;
+
+    let mut expected = Vec::new();
+    network.fire(vec![0.8], &mut expected)?;
+
+    let mut output = [0.0; 1];
+    network.fire_into(&[0.8], &mut output)?;
+    assert_eq!(output.to_vec(), expected);
+
+    let mut wrong_size = [0.0; 2];
+    assert!(network.fire_into(&[0.8], &mut wrong_size).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_layer_ordering() {
+    use crate::layer::LayerID;
+    use std::cmp::Ordering;
+
+    // in ascending order
+    let layers = [
+        LayerID::InputLayer,
+        LayerID::HiddenLayer(0),
+        LayerID::HiddenLayer(1),
+        LayerID::HiddenLayer(7),
+        LayerID::OutputLayer,
+    ];
+
+    for (i, a) in layers.iter().enumerate() {
+        for (j, b) in layers.iter().enumerate() {
+            assert_eq!(a.cmp(b), i.cmp(&j), "{:?} cmp {:?}", a, b);
+            assert_eq!(a.partial_cmp(b), Some(i.cmp(&j)));
+            assert_eq!(a == b, a.cmp(b) == Ordering::Equal);
+        }
+    }
+
+    let mut shuffled = vec![
+        LayerID::HiddenLayer(7),
+        LayerID::OutputLayer,
+        LayerID::HiddenLayer(0),
+        LayerID::InputLayer,
+        LayerID::HiddenLayer(1),
+    ];
+    shuffled.sort();
+
+    assert_eq!(shuffled, layers.to_vec());
+}
+
+#[test]
+fn test_fire_layer_order() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let mut network = Network::create(1, 1, ActivationFn::Sigmoid)?;
+    let input_node_id = network.input_node_ids().pop().unwrap();
+    let output_node_id = network.output_node_ids().pop().unwrap();
+    Edge::create(&mut network, input_node_id, output_node_id, 1.0)?;
+
+    let mut output = vec![];
+    network.fire(vec![0.0], &mut output)?;
+
+    // the output activation is only applied once
+    assert_eq!(output, vec![0.5]);
+
+    let first = network.add_layer();
+    let second = network.add_layer();
+    let first_node_id = Node::create(&mut network, first, 1.0)?;
+    let second_node_id = Node::create(&mut network, second, 0.0)?;
+    Edge::create(&mut network, input_node_id, first_node_id, 1.0)?;
+    Edge::create(&mut network, first_node_id, second_node_id, 1.0)?;
+    Edge::create(&mut network, second_node_id, output_node_id, 1.0)?;
+
+    // hidden layers are created after the output layer, but still fire before it
+    network.layers.reverse();
+    network.fire(vec![0.0], &mut output)?;
+
+    let sigmoid = |x: Float| 1.0 / (1.0 + (-x).exp());
+    assert_eq!(output, vec![sigmoid(sigmoid(sigmoid(1.0)))]);
+
+    Ok(())
+}
+
+#[test]
+fn test_feedforward() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerID, network::Network};
+
+    let mut network = Network::feedforward(&[2, 3, 4, 1], ActivationFn::ReLU)?;
+
+    assert_eq!(network.input_size(), 2);
+    assert_eq!(network.layer_node_ids(LayerID::HiddenLayer(0))?.len(), 3);
+    assert_eq!(network.layer_node_ids(LayerID::HiddenLayer(1))?.len(), 4);
+    assert_eq!(network.output_size(), 1);
+    assert_eq!(network.edges.len(), 2 * 3 + 3 * 4 + 4);
+
+    let mut output = vec![];
+    network.fire(vec![0.5, 0.5], &mut output)?;
+    assert_eq!(output.len(), 1);
+
+    assert!(Network::feedforward(&[2], ActivationFn::ReLU).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_input_ordering() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network, node::Node,
+    };
+
+    let mut network = Network::create(2, 1, ActivationFn::Linear)?;
+    let removed_id = network.input_node_ids()[0];
+    network.remove_node(removed_id)?;
+
+    let added_id = Node::create(&mut network, LayerID::InputLayer, 0.0)?;
+    let kept_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+
+    Edge::create(&mut network, kept_id, output_node_id, 1.0)?;
+    Edge::create(&mut network, added_id, output_node_id, 10.0)?;
+
+    // storage order must not affect which node receives which input
+    network.nodes.reverse();
+
+    assert_eq!(network.input_node_ids(), vec![kept_id, added_id]);
+
+    let mut output = vec![];
+    network.fire(vec![1.0, 0.0], &mut output)?;
+    assert_eq!(output, vec![1.0]);
+
+    network.fire(vec![0.0, 1.0], &mut output)?;
+    assert_eq!(output, vec![10.0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_recurrent() -> anyhow::Result<()> {
+    use crate::{edge::Edge, layer::LayerID};
+
+    let mut network = test_creation()?;
+    let hidden_node_id = network.layer_node_ids(LayerID::HiddenLayer(0))?[0];
+    let output_node_id = network.output_node_ids()[0];
+
+    assert!(Edge::create(&mut network, output_node_id, hidden_node_id, 0.5).is_err());
+    Edge::create_recurrent(&mut network, output_node_id, hidden_node_id, 0.5)?;
+
+    // single-step firing ignores recurrent edges
+    let mut output = vec![];
+    network.fire(vec![0.8], &mut output)?;
+    let first = (0.8 * 2.0) + (((0.8 * 1.3) + 0.2) * 1.5);
+    assert_eq!(output, vec![first]);
+
+    let outputs = network.fire_sequence(&[vec![0.8], vec![0.8]])?;
+    let second = (0.8 * 2.0) + (((first * 0.5) + (0.8 * 1.3) + 0.2) * 1.5);
+    assert_eq!(outputs, vec![vec![first], vec![second]]);
+
+    Ok(())
+}
+
+#[test]
+fn test_push_dense() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerSpec, network::Network};
+
+    let mut network = Network::create(2, 1, ActivationFn::Linear)?;
+
+    let first = network.push_dense(LayerSpec {
+        size: 3,
+        activation: ActivationFn::ReLU,
+    })?;
+    let second = network.push_dense(LayerSpec {
+        size: 2,
+        activation: ActivationFn::Tanh,
+    })?;
+
+    let first_ids = network.layer_node_ids(first)?;
+    let second_ids = network.layer_node_ids(second)?;
+
+    assert_eq!(first_ids.len(), 3);
+    assert_eq!(second_ids.len(), 2);
+    assert_eq!(network.edges.len(), 2 * 3 + 3 * 2);
+
+    // the second layer is only fed by the first
+    assert!(network
+        .edges
+        .iter()
+        .filter(|edge| second_ids.contains(&edge.node_to_id))
+        .all(|edge| first_ids.contains(&edge.node_from_id)));
+
+    assert!(second_ids
+        .iter()
+        .all(|id| network.get_node(*id).unwrap().activation_fn == ActivationFn::Tanh));
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_and_capture() -> anyhow::Result<()> {
+    use crate::layer::LayerID;
+
+    let mut network = test_creation()?;
+    let input_node_id = network.input_node_ids()[0];
+    let hidden_node_id = network.layer_node_ids(LayerID::HiddenLayer(0))?[0];
+    let output_node_id = network.output_node_ids()[0];
+
+    let values = network.fire_and_capture(vec![0.8])?;
+
+    assert_eq!(values.len(), 3);
+    assert_eq!(values[&input_node_id], 0.8);
+    assert_eq!(values[&hidden_node_id], (0.8 * 1.3) + 0.2);
+
+    let mut output = vec![];
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(values[&output_node_id], output[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_merge() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network};
+
+    let mut other = test_creation()?;
+    other.freeze_layer(LayerID::HiddenLayer(0))?;
+    let mut expected = vec![];
+    other.fire(vec![0.8], &mut expected)?;
+
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let ids = network.merge(&other)?;
+
+    // frozen nodes and edges stay frozen
+    for node in other.nodes.iter() {
+        assert_eq!(network.get_node(ids[&node.id]).unwrap().frozen, node.frozen);
+    }
+    assert_eq!(network.nodes.iter().filter(|node| node.frozen).count(), 1);
+    assert_eq!(network.edges.iter().filter(|edge| edge.frozen).count(), 1);
+    let frozen_edge = network.edges.iter().find(|edge| edge.frozen).unwrap();
+    assert_eq!(frozen_edge.weight, 1.3);
+
+    assert_eq!(ids.len(), 3);
+    assert_eq!(network.nodes.len(), 5);
+    assert_eq!(network.edges.len(), 3);
+    assert!(ids
+        .values()
+        .all(|id| network.get_node(*id).unwrap().layer_id.is_hidden()));
+    assert_eq!(
+        network
+            .get_node(ids[&other.input_node_ids()[0]])
+            .unwrap()
+            .layer_id,
+        LayerID::HiddenLayer(0)
+    );
+
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    Edge::create(
+        &mut network,
+        input_node_id,
+        ids[&other.input_node_ids()[0]],
+        1.0,
+    )?;
+    Edge::create(
+        &mut network,
+        ids[&other.output_node_ids()[0]],
+        output_node_id,
+        1.0,
+    )?;
+
+    // the merged nodes compute the same function as the original network
+    let mut output = vec![];
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_to_dot() -> anyhow::Result<()> {
+    let network = test_creation()?;
+
+    assert_eq!(
+        network.to_dot(),
+        r#"digraph network {
+    rankdir=LR;
+    subgraph cluster_0 {
+        label="InputLayer";
+        rank=same;
+        n1 [label="1\nbias: 0\nLinear", style=filled, fillcolor=lightblue];
+    }
+    subgraph cluster_1 {
+        label="HiddenLayer(0)";
+        rank=same;
+        n3 [label="3\nbias: 0.2\nReLU", style=filled, fillcolor=lightgray];
+    }
+    subgraph cluster_2 {
+        label="OutputLayer";
+        rank=same;
+        n2 [label="2\nbias: 0\nReLU", style=filled, fillcolor=lightgreen];
+    }
+    n1 -> n3 [label="1.3"];
+    n3 -> n2 [label="1.5"];
+    n1 -> n2 [label="2"];
+}
+"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_quantized_xor() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network, node::Node,
+    };
+
+    let mut network = Network::create(2, 1, ActivationFn::ReLU)?;
+    network.set_layer_activation(LayerID::OutputLayer, ActivationFn::Linear)?;
+    let hidden_id = network.add_layer();
+
+    let inputs = network.input_node_ids();
+    let output_node_id = network.output_node_ids()[0];
+    let any = Node::create(&mut network, hidden_id, 0.0)?;
+    let both = Node::create(&mut network, hidden_id, -1.0)?;
+
+    for input in inputs {
+        Edge::create(&mut network, input, any, 1.0)?;
+        Edge::create(&mut network, input, both, 1.0)?;
+    }
+
+    Edge::create(&mut network, any, output_node_id, 1.0)?;
+    Edge::create(&mut network, both, output_node_id, -2.0)?;
+
+    let scale = 1000.0;
+    let quantized = network.quantize(scale)?;
+    let mut output = vec![];
+
+    for (a, b) in [(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0), (0.3, 0.9)] {
+        network.fire(vec![a, b], &mut output)?;
+
+        let quantized_output =
+            quantized.fire(&[(a * scale) as i32, (b * scale) as i32])?[0] as Float / scale;
+
+        assert!((quantized_output - output[0]).abs() < 0.01);
+    }
+
+    assert!(network.quantize(0.5).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_activation() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerID, node::Node};
+
+    let mut network = test_creation()?;
+    let hidden_node_id = network.layer_node_ids(LayerID::HiddenLayer(0))?[0];
+
+    Node::set_activation_closure(&mut network, hidden_node_id, |x| x * x)?;
+    assert_eq!(
+        network.layer_activation_fn(LayerID::HiddenLayer(0)),
+        ActivationFn::ReLU
+    );
+
+    let hidden = (0.8 * 1.3) + 0.2;
+    let mut output = vec![];
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, vec![(0.8 * 2.0) + (Float::powi(hidden, 2) * 1.5)]);
+
+    let jacobian = network.jacobian(&[0.8])?;
+    assert!((jacobian[0][0] - (2.0 + 2.0 * hidden * 1.3 * 1.5)).abs() < 1e-3);
+
+    let quantized = network.quantize(1000.0)?;
+    let quantized_output = quantized.fire(&[800])?[0] as Float / 1000.0;
+    assert!((quantized_output - output[0]).abs() < 0.01);
+
+    let mut merged = test_creation()?;
+    merged.merge(&network)?;
+    assert!(merged.serialize().is_err());
+
+    assert!(network.serialize().is_err());
+
+    Node::clear_activation_closure(&mut network, hidden_node_id)?;
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, vec![(0.8 * 2.0) + (hidden * 1.5)]);
+    assert!(network.serialize().is_ok());
+
+    assert!(Node::set_activation_closure(&mut network, 100, |x| x).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_gelu() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, network::Network};
+
+    let gelu = ActivationFn::GELU;
+
+    assert_eq!(gelu.run(0.0), 0.0);
+    assert!((gelu.run(1.0) - 0.841192).abs() < 1e-6);
+    assert!((gelu.run(-1.0) + 0.158808).abs() < 1e-6);
+
+    let samples = [0.0, 0.5, 1.0, 2.0, 3.0]
+        .iter()
+        .map(|x| gelu.run(*x))
+        .collect::<Vec<Float>>();
+    assert!(samples.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let network = Network::create(1, 1, ActivationFn::GELU)?;
+    let deserialized = Network::deserialized(&network.serialize()?)?;
+    assert_eq!(deserialized.activation_fn, ActivationFn::GELU);
+
+    Ok(())
+}
+
+#[test]
+fn test_parametric_activations() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, network::Network};
+
+    let sigmoid = ActivationFn::ParametricSigmoid(1.0);
+    let steep = ActivationFn::ParametricSigmoid(4.0);
+
+    for x in [-2.0, -0.5, 0.0, 0.5, 2.0] {
+        assert_eq!(sigmoid.run(x), ActivationFn::Sigmoid.run(x));
+        assert_eq!(steep.run(x), ActivationFn::Sigmoid.run(4.0 * x));
+        assert_eq!(ActivationFn::ScaledTanh(2.5).run(x), 2.5 * Float::tanh(x));
+    }
+
+    // derivatives match central differences
+    let h = 1e-3;
+    for activation_fn in [steep, ActivationFn::ScaledTanh(-1.5)] {
+        for x in [-1.0, 0.0, 0.3, 2.0] {
+            let numeric = (activation_fn.run(x + h) - activation_fn.run(x - h)) / (2.0 * h);
+            assert!((activation_fn.derivative(x) - numeric).abs() < 1e-3);
+        }
+    }
+
+    assert_eq!(
+        ActivationFn::ScaledTanh(-1.5).output_range(),
+        (Some(-1.5), Some(1.5))
+    );
+
+    let network = Network::create(1, 1, ActivationFn::ScaledTanh(0.75))?;
+    let deserialized = Network::deserialized(&network.serialize()?)?;
+    assert_eq!(deserialized.activation_fn, ActivationFn::ScaledTanh(0.75));
+
+    let network = Network::create(1, 1, ActivationFn::ParametricSigmoid(3.0))?;
+    let deserialized = Network::deserialized(&network.serialize()?)?;
+    assert_eq!(
+        deserialized.activation_fn,
+        ActivationFn::ParametricSigmoid(3.0)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_evaluate_classification() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+
+    let mut network = Network::create(3, 3, ActivationFn::Linear)?;
+
+    for (input, output) in network
+        .input_node_ids()
+        .into_iter()
+        .zip(network.output_node_ids())
+    {
+        Edge::create(&mut network, input, output, 1.0)?;
+    }
+
+    let inputs = vec![
+        vec![1.0, 0.0, 0.0],
+        vec![0.0, 1.0, 0.0],
+        vec![0.0, 0.0, 1.0],
+        vec![0.0, 1.0, 0.0],
+    ];
+    let report = network.evaluate_classification(&inputs, &[0, 1, 2, 2])?;
+
+    assert_eq!(report.accuracy, 0.75);
+    assert_eq!(
+        report.confusion_matrix,
+        vec![vec![1, 0, 0], vec![0, 1, 0], vec![0, 1, 1]]
+    );
+
+    assert!(network
+        .evaluate_classification(&inputs, &[0, 1, 2, 3])
+        .is_err());
+    assert!(network.evaluate_classification(&inputs, &[0, 1]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_depth() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, network::Network};
+    use std::collections::HashSet;
+
+    // the skip connection from input to output doesn't shorten the longest path
+    let network = test_creation()?;
+    assert_eq!(network.depth(), 2);
+
+    let disconnected = Network::create(2, 2, ActivationFn::Linear)?;
+    assert_eq!(disconnected.depth(), 0);
+
+    // a disabled edge is not a connection
+    let mut network = test_creation()?;
+    let edge_id = network
+        .edges
+        .iter()
+        .find(|edge| edge.node_from_id == 3)
+        .unwrap()
+        .id;
+    network.set_edge_enabled(edge_id, false)?;
+    assert_eq!(network.depth(), 1);
+    assert_eq!(network.compute_order(), vec![vec![1], vec![2, 3]]);
+    assert_eq!(network.reachable_from(3)?, HashSet::new());
+    assert_eq!(network.feeding_into(2)?, HashSet::from([1]));
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_cache() -> anyhow::Result<()> {
+    use crate::{edge::Edge, layer::LayerID, node::Node};
+
+    let mut network = test_creation()?;
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+
+    let mut output = vec![];
+    network.fire(vec![0.8], &mut output)?;
+    assert!(network.cache.is_some());
+
+    // structural changes after firing are picked up by the next fire
+    let hidden_node_id = Node::create(&mut network, LayerID::HiddenLayer(0), 0.0)?;
+    Edge::create(&mut network, input_node_id, hidden_node_id, 1.0)?;
+    Edge::create(&mut network, hidden_node_id, output_node_id, 1.0)?;
+
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(
+        output,
+        vec![(0.8 * 2.0) + (((0.8 * 1.3) + 0.2) * 1.5) + 0.8]
+    );
+
+    network.remove_node(hidden_node_id)?;
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, vec![(0.8 * 2.0) + (((0.8 * 1.3) + 0.2) * 1.5)]);
+
+    Ok(())
+}
+
+#[test]
+fn test_bias_node() -> anyhow::Result<()> {
+    use crate::{edge::Edge, layer::LayerID};
+
+    let mut network = test_creation()?;
+    let hidden_node_id = network.layer_node_ids(LayerID::HiddenLayer(0))?[0];
+    let output_node_id = network.output_node_ids()[0];
+
+    let input_bias_id = network.add_bias_node(LayerID::InputLayer)?;
+    let hidden_bias_id = network.add_bias_node(LayerID::HiddenLayer(0))?;
+    assert!(network.add_bias_node(LayerID::OutputLayer).is_err());
+
+    assert_eq!(network.input_size(), 1);
+    assert!(Edge::create(&mut network, input_bias_id, hidden_bias_id, 1.0).is_err());
+
+    Edge::create(&mut network, input_bias_id, hidden_node_id, 0.1)?;
+    Edge::create(&mut network, hidden_bias_id, output_node_id, 0.5)?;
+
+    let mut output = vec![];
+
+    // the bias nodes keep their value across fires
+    for _ in 0..2 {
+        network.fire(vec![0.8], &mut output)?;
+        assert_eq!(
+            output,
+            vec![(0.8 * 2.0) + (((0.8 * 1.3) + 0.1 + 0.2) * 1.5) + 0.5]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_compute_order() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let first_layer = network.add_layer();
+    let second_layer = network.add_layer();
+
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    let first_id = Node::create(&mut network, first_layer, 0.0)?;
+    let second_id = Node::create(&mut network, second_layer, 0.0)?;
+    let unused_id = Node::create(&mut network, first_layer, 0.0)?;
+
+    // input skips the first hidden layer entirely
+    Edge::create(&mut network, input_node_id, second_id, 1.0)?;
+    Edge::create(&mut network, second_id, output_node_id, 1.0)?;
+    Edge::create(&mut network, first_id, output_node_id, 1.0)?;
+
+    assert_eq!(
+        network.compute_order(),
+        vec![
+            vec![input_node_id, first_id, unused_id],
+            vec![second_id],
+            vec![output_node_id]
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_freeze_layer() -> anyhow::Result<()> {
+    use crate::{layer::LayerID, network::Network};
+
+    let mut network = test_creation()?;
+    let hidden_id = LayerID::HiddenLayer(0);
+
+    network.freeze_layer(hidden_id)?;
+    assert!(network.is_layer_frozen(hidden_id)?);
+    assert!(!network.is_layer_frozen(LayerID::OutputLayer)?);
+
+    // only the edge into the hidden node is frozen
+    assert_eq!(network.edges.iter().filter(|edge| edge.frozen).count(), 1);
+
+    let deserialized = Network::deserialized(&network.serialize()?)?;
+    assert!(deserialized.is_layer_frozen(hidden_id)?);
+
+    network.unfreeze_layer(hidden_id)?;
+    assert!(!network.is_layer_frozen(hidden_id)?);
+    assert!(network.freeze_layer(LayerID::HiddenLayer(3)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_connect_layers() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network, node::Node,
+        weightinit::WeightInit,
+    };
+
+    let mut network = Network::create(2, 2, ActivationFn::Linear)?;
+    let layer_id = network.add_layer();
+    Node::create(&mut network, layer_id, 0.0)?;
+    Node::create(&mut network, layer_id, 0.0)?;
+    network.add_bias_node(layer_id)?;
+
+    let input_node_ids = network.input_node_ids();
+    let hidden_node_ids = network.layer_node_ids(layer_id)?;
+    Edge::create(&mut network, input_node_ids[0], hidden_node_ids[0], 0.5)?;
+
+    // the existing edge and the bias node are skipped
+    let edge_ids =
+        network.connect_layers(LayerID::InputLayer, layer_id, WeightInit::Constant(2.0))?;
+    assert_eq!(edge_ids.len(), 3);
+    assert!(edge_ids
+        .iter()
+        .all(|id| network.get_edge(*id).unwrap().weight == 2.0));
+
+    // connecting again creates nothing
+    assert!(network
+        .connect_layers(LayerID::InputLayer, layer_id, WeightInit::He)?
+        .is_empty());
+
+    let edge_ids = network.connect_layers(layer_id, LayerID::OutputLayer, WeightInit::Xavier)?;
+    let limit = Float::sqrt(6.0 / 5.0);
+    assert_eq!(edge_ids.len(), 6);
+    assert!(edge_ids
+        .iter()
+        .all(|id| network.get_edge(*id).unwrap().weight.abs() <= limit));
+
+    assert!(network
+        .connect_layers(layer_id, layer_id, WeightInit::He)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_errors() -> anyhow::Result<()> {
+    use crate::{edge::Edge, layer::LayerID, network::Network};
+
+    let original = test_creation()?;
+    let mut output = vec![];
+
+    let mut network = Network::deserialized(&original.serialize()?)?;
+    network.edges.push(Edge {
+        id: 10,
+        weight: 1.0,
+        node_from_id: 1,
+        node_to_id: 7,
+        recurrent: false,
+        lateral: false,
+        frozen: false,
+        enabled: true,
+    });
+    let error = network.fire(vec![0.8], &mut output).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Edge 10 from node 1 in layer InputLayer goes to node 7, which does not exist"
+    );
+
+    let mut network = Network::deserialized(&original.serialize()?)?;
+    network.edges[0].node_from_id = 9;
+    let error = network.fire(vec![0.8], &mut output).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Edge {} comes from node 9, which does not exist",
+            network.edges[0].id
+        )
+    );
+
+    let mut network = Network::deserialized(&original.serialize()?)?;
+    network.layers.retain(|layer| !layer.is_hidden());
+    let error = network.fire(vec![0.8], &mut output).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Node 3 is in layer {:?}, which does not exist",
+            LayerID::HiddenLayer(0)
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_lateral_edges() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let layer_id = network.add_layer();
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    let bias_node_id = network.add_bias_node(layer_id)?;
+    let first_id = Node::create(&mut network, layer_id, 0.0)?;
+    let second_id = Node::create(&mut network, layer_id, 0.0)?;
+    let third_id = Node::create(&mut network, layer_id, 0.0)?;
+
+    for node_id in [first_id, second_id, third_id] {
+        Edge::create(&mut network, input_node_id, node_id, 1.0)?;
+    }
+
+    // first -> second -> third, with a bias feeding the last node
+    Edge::create_lateral(&mut network, first_id, second_id, 2.0)?;
+    Edge::create_lateral(&mut network, second_id, third_id, 3.0)?;
+    Edge::create_lateral(&mut network, first_id, third_id, 1.0)?;
+    Edge::create_lateral(&mut network, bias_node_id, third_id, 0.5)?;
+    Edge::create(&mut network, third_id, output_node_id, 1.0)?;
+
+    assert!(Edge::create_lateral(&mut network, third_id, first_id, 1.0).is_err());
+    assert!(Edge::create_lateral(&mut network, input_node_id, first_id, 1.0).is_err());
+    assert!(Edge::create(&mut network, first_id, second_id, 1.0).is_err());
+
+    // first = 1, second = 1 + 2 * 1 = 3, third = 1 + 3 * 3 + 1 * 1 + 0.5
+    let mut output = vec![];
+    network.fire(vec![1.0], &mut output)?;
+    assert_eq!(output, vec![11.5]);
+
+    let lateral_edge_id = network.edges.iter().find(|edge| edge.lateral).unwrap().id;
+    assert!(network.split_edge(lateral_edge_id).is_err());
+
+    assert_eq!(
+        network.compute_order(),
+        vec![
+            vec![input_node_id, bias_node_id],
+            vec![first_id],
+            vec![second_id],
+            vec![third_id],
+            vec![output_node_id]
+        ]
+    );
+
+    let mut deserialized = Network::deserialized(&network.serialize()?)?;
+    deserialized.fire(vec![1.0], &mut output)?;
+    assert_eq!(output, vec![11.5]);
+
+    let mut merged = Network::create(1, 1, ActivationFn::Linear)?;
+    merged.merge(&network)?;
+    assert_eq!(merged.edges.iter().filter(|edge| edge.lateral).count(), 4);
+
+    Ok(())
+}
+
+#[test]
+fn test_parameter_count() -> anyhow::Result<()> {
+    use crate::{layer::LayerID, network::ParamCounts};
+
+    let mut network = test_creation()?;
+    network.add_bias_node(LayerID::HiddenLayer(0))?;
+
+    // the input node and the bias node have no trainable bias
+    assert_eq!(
+        network.parameter_count(),
+        ParamCounts {
+            weights: 3,
+            biases: 2
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_format_version() -> anyhow::Result<()> {
+    use crate::network::{Network, FORMAT_VERSION};
+
+    let network = test_creation()?;
+    let serialized = network.serialize()?;
+    assert!(serialized.contains(&format!("\"version\":{}", FORMAT_VERSION)));
+
+    // networks saved before versioning, and before edges were tagged
+    let mut value: serde_json::Value = serde_json::from_str(&serialized)?;
+    value.as_object_mut().unwrap().remove("version");
+    for edge in value["edges"].as_array_mut().unwrap() {
+        let edge = edge.as_object_mut().unwrap();
+        edge.remove("recurrent");
+        edge.remove("lateral");
+        edge.remove("frozen");
+    }
+
+    let mut old = Network::deserialized(&value.to_string())?;
+    let mut output = vec![];
+    old.fire(vec![0.8], &mut output)?;
+    assert_eq!(output.len(), 1);
+
+    value["version"] = serde_json::Value::from(FORMAT_VERSION + 1);
+    let error = Network::deserialized(&value.to_string()).unwrap_err();
+    assert!(error.to_string().contains("only versions up to"));
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_sampled() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut network = Network::create(1, 3, ActivationFn::Linear)?;
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_ids = network.output_node_ids();
+    Edge::create(&mut network, input_node_id, output_node_ids[0], 1.0)?;
+    Edge::create(&mut network, input_node_id, output_node_ids[1], 2.0)?;
+    Edge::create(&mut network, input_node_id, output_node_ids[2], 0.0)?;
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut counts = [0; 3];
+
+    for _ in 0..1000 {
+        counts[network.fire_sampled(vec![1.0], 1e-3, &mut rng)?] += 1;
+    }
+    assert_eq!(counts, [0, 1000, 0]);
+
+    // softmax([1, 2, 0]) is roughly [0.24, 0.67, 0.09]
+    let mut counts = [0; 3];
+
+    for _ in 0..1000 {
+        counts[network.fire_sampled(vec![1.0], 1.0, &mut rng)?] += 1;
+    }
+    assert!(counts[1] > counts[0] && counts[0] > counts[2] && counts[2] > 0);
+
+    assert!(network.fire_sampled(vec![1.0], -1.0, &mut rng).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_sparsify() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, network::Network};
+
+    let mut network = test_creation()?;
+    let mut output = vec![];
+
+    // under the cap, nothing changes
+    let report = network.sparsify(3);
+    assert_eq!(report.edges_removed, 0);
+    assert_eq!(report.connected_outputs, 1);
+
+    // input -> hidden (1.3) is the weakest edge, which leaves the hidden
+    // node without inputs but still connected to the output
+    let report = network.sparsify(2);
+    assert_eq!(report.edges_removed, 1);
+    assert_eq!(report.nodes_removed, 0);
+    assert_eq!(network.edges.len(), 2);
+
+    // hidden -> output (1.5) is next; the hidden node is then removed
+    let report = network.sparsify(1);
+    assert_eq!(report.edges_removed, 1);
+    assert_eq!(report.nodes_removed, 1);
+    assert_eq!(report.connected_outputs, 1);
+    network.fire(vec![1.0], &mut output)?;
+    assert_eq!(output, vec![2.0]);
+
+    let report = network.sparsify(0);
+    assert_eq!(report.edges_removed, 1);
+    assert_eq!(report.connected_outputs, 0);
+
+    // a dense network of ordinary size sparsifies quickly, keeping every
+    // output connected
+    let mut network = Network::feedforward(&[100, 100, 10], ActivationFn::ReLU)?;
+    let report = network.sparsify(500);
+    assert!(network.edges.len() <= 500);
+    assert_eq!(
+        report.edges_removed,
+        100 * 100 + 100 * 10 - network.edges.len()
+    );
+    assert_eq!(report.connected_outputs, 10);
+    assert!(network.is_fully_connected());
+
+    Ok(())
+}
+
+#[test]
+fn test_skeletonize() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    // input -> hidden -> output (1.3 + 1.5) outweighs input -> output (2.0)
+    let mut network = test_creation()?;
+    let report = network.skeletonize();
+    assert_eq!(report.edges_removed, 1);
+    assert_eq!(report.nodes_removed, 0);
+    assert_eq!(report.connected_outputs, 1);
+    assert!(network.edges.iter().all(|edge| edge.weight != 2.0));
+
+    let mut network = Network::create(2, 2, ActivationFn::Linear)?;
+    let layer_id = network.add_layer();
+    let inputs = network.input_node_ids();
+    let outputs = network.output_node_ids();
+    let a = Node::create(&mut network, layer_id, 0.0)?;
+    let b = Node::create(&mut network, layer_id, 0.0)?;
+    let bias = network.add_bias_node(layer_id)?;
+
+    let kept = [
+        Edge::create(&mut network, inputs[0], a, -3.0)?,
+        Edge::create(&mut network, a, outputs[0], 1.0)?,
+        Edge::create(&mut network, a, outputs[1], 0.5)?,
+    ];
+    Edge::create(&mut network, inputs[1], a, 2.0)?;
+    Edge::create(&mut network, inputs[1], b, 1.0)?;
+    Edge::create(&mut network, b, outputs[0], 2.5)?;
+    Edge::create(&mut network, b, outputs[1], 0.1)?;
+    Edge::create(&mut network, bias, outputs[1], 10.0)?;
+
+    // a -> output 0 (3.0 + 1.0) beats b -> output 0 (1.0 + 2.5), and the
+    // bias node is not an input, so its heavy edge is not on any path
+    let report = network.skeletonize();
+    assert_eq!(report.edges_removed, 5);
+    assert_eq!(report.nodes_removed, 2);
+    assert_eq!(report.connected_outputs, 2);
+
+    let mut edge_ids = network
+        .edges
+        .iter()
+        .map(|edge| edge.id)
+        .collect::<Vec<usize>>();
+    edge_ids.sort();
+    assert_eq!(edge_ids, kept);
+    assert!(network.get_node(b).is_none());
+    assert!(network.get_node(bias).is_none());
+
+    let mut output = vec![];
+    network.fire(vec![1.0, 1.0], &mut output)?;
+    assert_eq!(output, vec![-3.0, -1.5]);
+
+    Ok(())
+}
+
+#[test]
+fn test_csv() -> anyhow::Result<()> {
+    use crate::data::{load_csv, save_csv};
+    use std::{env, fs};
+
+    let (inputs, targets) = load_csv(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/xor.csv"), 2)?;
+    assert_eq!(
+        inputs,
+        vec![
+            vec![0.0, 0.0],
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+            vec![1.0, 1.0]
+        ]
+    );
+    assert_eq!(targets, vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]]);
+
+    let dir = env::temp_dir();
+    let path = dir.join("nnrs_test_csv.csv");
+    let path = path.to_str().unwrap();
+
+    save_csv(path, &inputs, &targets)?;
+    assert_eq!(load_csv(path, 2)?, (inputs, targets));
+
+    fs::write(path, "1,2,3\n4,5\n")?;
+    let error = load_csv(path, 2).unwrap_err();
+    assert_eq!(error.to_string(), "Row 2 has 2 columns, expected 3");
+
+    fs::write(path, "1,2,3\n4,five,6\n")?;
+    let error = load_csv(path, 2).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Row 2, column 2: \"five\" is not a number"
+    );
+
+    // only a first row without any numbers is a header
+    fs::write(path, "a,b,c\n1,2,3\n")?;
+    assert_eq!(load_csv(path, 2)?, (vec![vec![1.0, 2.0]], vec![vec![3.0]]));
+
+    fs::write(path, "1,x,0\n1,2,3\n")?;
+    let error = load_csv(path, 2).unwrap_err();
+    assert_eq!(error.to_string(), "Row 1, column 2: \"x\" is not a number");
+
+    fs::remove_file(path)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_jacobian() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerID, network::Network, Float};
+
+    let mut example = test_creation()?;
+    let mut gelu = Network::feedforward(&[3, 4, 4, 2], ActivationFn::GELU)?;
+    gelu.set_layer_activation(LayerID::OutputLayer, ActivationFn::Sigmoid)?;
+
+    let h = 1e-3;
+
+    for (network, inputs) in [(&mut example, vec![0.8]), (&mut gelu, vec![0.3, -0.5, 0.9])] {
+        let jacobian = network.jacobian(&inputs)?;
+        assert_eq!(jacobian.len(), network.output_size());
+
+        for input in 0..inputs.len() {
+            let mut plus = inputs.clone();
+            let mut minus = inputs.clone();
+            plus[input] += h;
+            minus[input] -= h;
+
+            let (mut outputs_plus, mut outputs_minus) = (vec![], vec![]);
+            network.fire(plus, &mut outputs_plus)?;
+            network.fire(minus, &mut outputs_minus)?;
+
+            for output in 0..jacobian.len() {
+                let estimate: Float = (outputs_plus[output] - outputs_minus[output]) / (2.0 * h);
+                assert!((jacobian[output][input] - estimate).abs() < 1e-2);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_export_parameters() -> anyhow::Result<()> {
+    let mut network = test_creation()?;
+
+    // edges 1 -> 3, 3 -> 2 and 1 -> 2, then the output and hidden biases
+    let parameters = network.export_parameters();
+    assert_eq!(parameters, vec![1.3, 1.5, 2.0, 0.0, 0.2]);
+
+    network.import_parameters(&[1.0, 1.0, 1.0, 0.5, 0.5])?;
+    let mut output = vec![];
+    network.fire(vec![1.0], &mut output)?;
+    assert_eq!(output, vec![((1.0 + 0.5) * 1.0 + 1.0) + 0.5]);
+
+    assert!(network.import_parameters(&parameters[..4]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_clamp_weights() -> anyhow::Result<()> {
+    let mut network = test_creation()?;
+
+    network.clamp_weights(-1.4, 1.4)?;
+    assert_eq!(network.export_parameters(), vec![1.3, 1.4, 1.4, 0.0, 0.2]);
+
+    assert!(network.clamp_weights(1.0, -1.0).is_err());
+    assert!(network.clamp_weights(0.0, Float::NAN).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_ids_not_reused() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let layer_id = network.add_layer();
+    let input_node_id = network.input_node_ids()[0];
+
+    let mut node_ids = vec![];
+    let mut edge_ids = vec![];
+
+    for _ in 0..3 {
+        let node_id = Node::create(&mut network, layer_id, 0.0)?;
+        node_ids.push(node_id);
+        edge_ids.push(Edge::create(&mut network, input_node_id, node_id, 1.0)?);
+    }
+
+    // removing the newest node also removes the newest edge
+    let top_node_id = *node_ids.iter().max().unwrap();
+    let top_edge_id = *edge_ids.iter().max().unwrap();
+    network.remove_node(top_node_id)?;
+
+    let node_id = Node::create(&mut network, layer_id, 0.0)?;
+    let edge_id = Edge::create(&mut network, input_node_id, node_id, 1.0)?;
+    assert!(node_id > top_node_id);
+    assert!(edge_id > top_edge_id);
+
+    // the counters survive serialization
+    network.remove_node(node_id)?;
+    let mut deserialized = Network::deserialized(&network.serialize()?)?;
+    assert!(Node::create(&mut deserialized, layer_id, 0.0)? > node_id);
+
+    // networks saved before the counters existed continue after the max id
+    let mut value: serde_json::Value = serde_json::from_str(&network.serialize()?)?;
+    let object = value.as_object_mut().unwrap();
+    object.remove("next_node_id");
+    object.remove("next_edge_id");
+    let mut old = Network::deserialized(&value.to_string())?;
+    let max_id = old.nodes.iter().map(|node| node.id).max().unwrap();
+    assert_eq!(Node::create(&mut old, layer_id, 0.0)?, max_id + 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_activation_stats() -> anyhow::Result<()> {
+    let mut network = test_creation()?;
+
+    // hidden = relu(1.3 * x + 0.2), which is dead for x = -1
+    let inputs = vec![vec![-1.0], vec![0.0], vec![1.0]];
+    let stats = network.activation_stats(&inputs)?;
+
+    let input = stats[&1];
+    assert_eq!(input.mean, 0.0);
+    assert!((input.variance - 2.0 / 3.0).abs() < 1e-6);
+    assert!((input.fraction_active - 2.0 / 3.0).abs() < 1e-6);
+
+    let hidden = stats[&3];
+    let values = [0.0, 0.2, 1.5];
+    let mean = values.iter().sum::<Float>() / 3.0;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<Float>() / 3.0;
+    assert!((hidden.mean - mean).abs() < 1e-6);
+    assert!((hidden.variance - variance).abs() < 1e-6);
+    assert!((hidden.fraction_active - 2.0 / 3.0).abs() < 1e-6);
+
+    assert_eq!(stats.len(), 3);
+    assert!(network.activation_stats(&[]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_ensemble() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn,
+        edge::Edge,
+        ensemble::{Ensemble, EnsembleMode},
+        network::Network,
+    };
+
+    // each member passes the input straight to one of two outputs
+    let member = |weights: [Float; 2]| -> anyhow::Result<Network> {
+        let mut network = Network::create(1, 2, ActivationFn::Linear)?;
+        let input_node_id = network.input_node_ids()[0];
+        for (output_node_id, weight) in network.output_node_ids().into_iter().zip(weights) {
+            Edge::create(&mut network, input_node_id, output_node_id, weight)?;
+        }
+        Ok(network)
+    };
+
+    let members = vec![
+        member([1.0, 0.0])?,
+        member([0.0, 3.0])?,
+        member([0.5, 0.0])?,
+    ];
+
+    let mut average = Ensemble::new(members.clone(), EnsembleMode::Average)?;
+    assert_eq!(average.fire(vec![2.0])?, vec![1.0, 2.0]);
+
+    let mut vote = Ensemble::new(members, EnsembleMode::MajorityVote)?;
+    let votes = vote.fire(vec![2.0])?;
+    assert!((votes[0] - 2.0 / 3.0).abs() < 1e-6);
+    assert!((votes[1] - 1.0 / 3.0).abs() < 1e-6);
+
+    let mismatched = vec![
+        member([1.0, 0.0])?,
+        Network::create(1, 1, ActivationFn::Linear)?,
+    ];
+    assert!(Ensemble::new(mismatched, EnsembleMode::Average).is_err());
+    assert!(Ensemble::new(vec![], EnsembleMode::Average).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_insert_layer() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network, node::Node,
+    };
+
+    let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU)?;
+    let hidden_node_ids = network.layer_node_ids(LayerID::HiddenLayer(0))?;
+    let inputs = vec![0.5, -0.25];
+
+    let mut expected = vec![];
+    network.fire(inputs.clone(), &mut expected)?;
+
+    // an empty layer before the existing hidden layer changes nothing
+    let layer_id = network.insert_layer(LayerID::InputLayer)?;
+    assert_eq!(layer_id, LayerID::HiddenLayer(0));
+    assert_eq!(
+        network.layer_node_ids(LayerID::HiddenLayer(1))?,
+        hidden_node_ids
+    );
+
+    let mut output = vec![];
+    network.fire(inputs.clone(), &mut output)?;
+    assert_eq!(output, expected);
+
+    // a node in the new layer can feed the shifted layer
+    let node_id = Node::create(&mut network, layer_id, 1.0)?;
+    Edge::create(&mut network, node_id, hidden_node_ids[0], 1.0)?;
+    assert!(Edge::create(&mut network, hidden_node_ids[1], node_id, 1.0).is_err());
+    network.fire(inputs, &mut output)?;
+
+    assert!(network.insert_layer(LayerID::OutputLayer).is_err());
+    assert!(network.insert_layer(LayerID::HiddenLayer(5)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_reachability() -> anyhow::Result<()> {
+    use crate::{edge::Edge, layer::LayerID, node::Node};
+    use std::collections::HashSet;
+
+    // input 1 -> hidden 3 -> output 2, with the skip connection 1 -> 2
+    let mut network = test_creation()?;
+    let dead_id = Node::create(&mut network, LayerID::HiddenLayer(0), 0.0)?;
+    Edge::create(&mut network, 1, dead_id, 1.0)?;
+
+    assert_eq!(network.reachable_from(1)?, HashSet::from([2, 3, dead_id]));
+    assert_eq!(network.reachable_from(3)?, HashSet::from([2]));
+    assert!(network.reachable_from(2)?.is_empty());
+
+    assert_eq!(network.feeding_into(2)?, HashSet::from([1, 3]));
+    assert_eq!(network.feeding_into(dead_id)?, HashSet::from([1]));
+
+    // the dead node does not influence any output
+    let influencing = network.feeding_into(2)?;
+    assert!(!influencing.contains(&dead_id));
+
+    // recurrent edges are followed, and cycles include the node itself
+    Edge::create_recurrent(&mut network, 2, 3, 0.5)?;
+    assert_eq!(network.reachable_from(2)?, HashSet::from([2, 3]));
+
+    assert!(network.reachable_from(100).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_disabled_edges() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, network::Network};
+
+    let mut network = test_creation()?;
+    let skip_edge_id = network
+        .edges
+        .iter()
+        .find(|edge| edge.node_from_id == 1 && edge.node_to_id == 2)
+        .unwrap()
+        .id;
+
+    network.set_edge_enabled(skip_edge_id, false)?;
+
+    let mut output = vec![];
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, vec![((0.8 * 1.3) + 0.2) * 1.5]);
+    assert_eq!(network.jacobian(&[0.8])?, vec![vec![1.3 * 1.5]]);
+
+    let quantized = network.quantize(1000.0)?;
+    assert_eq!(quantized.fire(&[800])?, vec![1860]);
+
+    // the flag survives serialization and merging
+    let mut deserialized = Network::deserialized(&network.serialize()?)?;
+    deserialized.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, vec![((0.8 * 1.3) + 0.2) * 1.5]);
+
+    let mut merged = Network::create(1, 1, ActivationFn::Linear)?;
+    merged.merge(&network)?;
+    assert_eq!(merged.edges.iter().filter(|edge| !edge.enabled).count(), 1);
+
+    // files without the flag load with every edge enabled
+    let mut value: serde_json::Value = serde_json::from_str(&network.serialize()?)?;
+    for edge in value["edges"].as_array_mut().unwrap() {
+        edge.as_object_mut().unwrap().remove("enabled");
+    }
+    let old = Network::deserialized(&value.to_string())?;
+    assert!(old.edges.iter().all(|edge| edge.enabled));
+
+    assert!(network.set_edge_enabled(100, true).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_map_parameters() -> anyhow::Result<()> {
+    let mut network = test_creation()?;
+    let mut output = vec![];
+
+    network.map_weights(|weight| weight * 0.0);
+    assert!(network.edges.iter().all(|edge| edge.weight == 0.0));
+
+    // with every weight zeroed, only the output bias reaches the output
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, vec![0.0]);
+
+    network.map_biases(|bias| bias + 0.5);
+    network.fire(vec![0.8], &mut output)?;
+    assert_eq!(output, vec![0.5]);
+
+    Ok(())
+}
+
+#[test]
+fn test_adjacency_matrix() -> anyhow::Result<()> {
+    let network = test_creation()?;
+    let (ids, matrix) = network.adjacency_matrix();
+
+    // input 1, hidden 3, output 2
+    assert_eq!(ids, vec![1, 3, 2]);
+    assert_eq!(matrix[0][1], 1.3);
+    assert_eq!(matrix[1][2], 1.5);
+    assert_eq!(matrix[0][2], 2.0);
+
+    // feed-forward, so everything on or below the diagonal is zero
+    for (row, weights) in matrix.iter().enumerate() {
+        assert!(weights[..=row].iter().all(|weight| *weight == 0.0));
+    }
+
+    // every edge appears exactly once
+    let nonzero = matrix.iter().flatten().filter(|weight| **weight != 0.0);
+    assert_eq!(nonzero.count(), network.edges.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_feedforward_invariant() -> anyhow::Result<()> {
+    use crate::{edge::Edge, layer::LayerID, node::Node};
+
+    let mut network = test_creation()?;
+    network.verify_feedforward_invariant()?;
+
+    // recurrent edges may point backwards
+    Edge::create_recurrent(&mut network, 2, 3, 1.0)?;
+    network.verify_feedforward_invariant()?;
+
+    let lateral_node_id = Node::create(&mut network, LayerID::HiddenLayer(0), 0.0)?;
+    Edge::create_lateral(&mut network, 3, lateral_node_id, 1.0)?;
+    network.verify_feedforward_invariant()?;
+
+    // moving the hidden node behind the input layer breaks 1 -> 3
+    let edge_id = network
+        .edges
+        .iter()
+        .find(|edge| edge.node_from_id == 1 && edge.node_to_id == 3)
+        .unwrap()
+        .id;
+    network.get_node_mut(3).unwrap().layer_id = LayerID::InputLayer;
+
+    let error = network.verify_feedforward_invariant().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Edge {} goes from layer InputLayer to layer InputLayer, which is not later",
+            edge_id
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_with_capacity() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, network::Network};
+
+    let network = Network::with_capacity(3, 2, ActivationFn::Sigmoid, 32, 128)?;
+    assert!(network.nodes.capacity() >= 32);
+    assert!(network.edges.capacity() >= 128);
+
+    let created = Network::create(3, 2, ActivationFn::Sigmoid)?;
+    assert_eq!(network.serialize()?, created.serialize()?);
+
+    Ok(())
+}
+
+#[test]
+fn test_create_classifier() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let mut network = Network::create_classifier(2, 3, ActivationFn::ReLU)?;
+    let layer_id = network.add_layer();
+    let hidden_node_id = Node::create(&mut network, layer_id, 0.0)?;
+    let input_node_ids = network.input_node_ids();
+    let output_node_ids = network.output_node_ids();
+
+    assert_eq!(
+        network.get_node(hidden_node_id).unwrap().activation_fn,
+        ActivationFn::ReLU
+    );
+    for output_node_id in output_node_ids.iter() {
+        assert_eq!(
+            network.get_node(*output_node_id).unwrap().activation_fn,
+            ActivationFn::Linear
+        );
+    }
+
+    Edge::create(&mut network, input_node_ids[0], hidden_node_id, 1.0)?;
+    Edge::create(&mut network, input_node_ids[1], output_node_ids[0], -2.0)?;
+    Edge::create(&mut network, hidden_node_id, output_node_ids[1], 3.0)?;
+    Edge::create(&mut network, hidden_node_id, output_node_ids[2], 1.0)?;
+
+    let mut outputs = vec![];
+    for inputs in [vec![0.0, 0.0], vec![1.0, -1.0], vec![-5.0, 40.0]] {
+        network.fire(inputs, &mut outputs)?;
+
+        let sum = outputs.iter().sum::<Float>();
+        assert!((sum - 1.0).abs() < 1e-6);
+        assert!(outputs.iter().all(|output| *output >= 0.0));
+    }
+
+    // logits [-2, 3, 1]
+    network.fire(vec![1.0, 1.0], &mut outputs)?;
+    let exps = [Float::exp(-2.0), Float::exp(3.0), Float::exp(1.0)];
+    let sum = exps.iter().sum::<Float>();
+    for (output, exp) in outputs.iter().zip(exps) {
+        assert!((output - exp / sum).abs() < 1e-6);
+    }
+
+    let mut loaded = Network::deserialized(&network.serialize()?)?;
+    let mut loaded_outputs = vec![];
+    loaded.fire(vec![1.0, 1.0], &mut loaded_outputs)?;
+    assert_eq!(outputs, loaded_outputs);
+
+    // plain networks are unchanged
+    let mut network = Network::create(1, 2, ActivationFn::Linear)?;
+    assert_eq!(
+        network
+            .get_node(network.output_node_ids()[0])
+            .unwrap()
+            .activation_fn,
+        ActivationFn::Linear
+    );
+    network.fire(vec![1.0], &mut outputs)?;
+    assert_eq!(outputs, vec![0.0, 0.0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_create_classifier_jacobian() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+
+    let mut network = Network::create_classifier(1, 2, ActivationFn::ReLU)?;
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_ids = network.output_node_ids();
+    Edge::create(&mut network, input_node_id, output_node_ids[0], 1.0)?;
+    Edge::create(&mut network, input_node_id, output_node_ids[1], -1.0)?;
+
+    // p0 = sigmoid(2x), so dp0/dx = 2 * p0 * (1 - p0)
+    let mut outputs = vec![];
+    network.fire(vec![0.3], &mut outputs)?;
+    let jacobian = network.jacobian(&[0.3])?;
+
+    let derivative = 2.0 * outputs[0] * (1.0 - outputs[0]);
+    assert!((jacobian[0][0] - derivative).abs() < 1e-6);
+    assert!((jacobian[1][0] + derivative).abs() < 1e-6);
+
+    Ok(())
+}
+
+#[test]
+fn test_count_paths() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    // a diamond: input -> {a, b} -> c -> output, plus input -> c
+    let mut network = Network::create(1, 1, ActivationFn::ReLU)?;
+    let first_layer = network.add_layer();
+    let second_layer = network.add_layer();
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    let a = Node::create(&mut network, first_layer, 0.0)?;
+    let b = Node::create(&mut network, first_layer, 0.0)?;
+    let c = Node::create(&mut network, second_layer, 0.0)?;
+
+    Edge::create(&mut network, input_node_id, a, 1.0)?;
+    Edge::create(&mut network, input_node_id, b, 1.0)?;
+    Edge::create(&mut network, a, c, 1.0)?;
+    Edge::create(&mut network, b, c, 1.0)?;
+    Edge::create(&mut network, input_node_id, c, 1.0)?;
+    Edge::create(&mut network, c, output_node_id, 1.0)?;
+
+    assert_eq!(network.count_paths(input_node_id, output_node_id), 3);
+    assert_eq!(network.count_paths(a, output_node_id), 1);
+    assert_eq!(network.count_paths(input_node_id, c), 3);
+    assert_eq!(network.count_paths(output_node_id, output_node_id), 1);
+    assert_eq!(network.count_paths(a, b), 0);
+    assert_eq!(network.count_paths(output_node_id, input_node_id), 0);
+    assert_eq!(network.count_paths(input_node_id, 1000), 0);
+
+    // a lateral edge adds a route through a to b
+    Edge::create_lateral(&mut network, a, b, 1.0)?;
+    assert_eq!(network.count_paths(input_node_id, output_node_id), 4);
+
+    // recurrent and disabled edges are not feed-forward paths
+    Edge::create_recurrent(&mut network, output_node_id, a, 1.0)?;
+    let edge_id = Edge::create(&mut network, a, output_node_id, 1.0)?;
+    network.set_edge_enabled(edge_id, false)?;
+    assert_eq!(network.count_paths(input_node_id, output_node_id), 4);
+    assert_eq!(network.count_paths(output_node_id, a), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_random_network() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, network::Network};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // every pair of nodes in different layers: 3*4 + 3*4 + 3*2 + 4*4 + 4*2 + 4*2
+    let network = Network::random(3, 2, 2, 4, 1.0, ActivationFn::ReLU, &mut rng)?;
+    assert_eq!(network.nodes.len(), 13);
+    assert_eq!(network.edges.len(), 62);
+    assert!(network.edges.iter().all(|edge| edge.weight.abs() <= 1.0));
+    network.verify_feedforward_invariant()?;
+
+    // with no random edges, chains are forced so every output is connected
+    for _ in 0..20 {
+        let network = Network::random(3, 2, 2, 4, 0.0, ActivationFn::ReLU, &mut rng)?;
+        let input_node_ids = network.input_node_ids();
+
+        assert!(network.edges.len() <= 6);
+        for output_node_id in network.output_node_ids() {
+            assert!(input_node_ids
+                .iter()
+                .any(|id| network.count_paths(*id, output_node_id) > 0));
+        }
+    }
+
+    let mut network = Network::random(2, 1, 0, 0, 0.0, ActivationFn::Linear, &mut rng)?;
+    assert_eq!(network.edges.len(), 1);
+    let mut outputs = vec![];
+    network.fire(vec![1.0, 1.0], &mut outputs)?;
+
+    // the same seed gives the same network
+    let first = Network::random(
+        2,
+        2,
+        1,
+        3,
+        0.5,
+        ActivationFn::Tanh,
+        &mut StdRng::seed_from_u64(7),
+    )?;
+    let second = Network::random(
+        2,
+        2,
+        1,
+        3,
+        0.5,
+        ActivationFn::Tanh,
+        &mut StdRng::seed_from_u64(7),
+    )?;
+    assert_eq!(first.serialize()?, second.serialize()?);
+
+    assert!(Network::random(2, 1, 1, 2, 1.5, ActivationFn::ReLU, &mut rng).is_err());
+    assert!(Network::random(0, 1, 1, 2, 0.5, ActivationFn::ReLU, &mut rng).is_err());
+    assert!(Network::random(2, 1, 1, 0, 0.5, ActivationFn::ReLU, &mut rng).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_diff() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn,
+        edge::Edge,
+        network::Network,
+        node::{Node, NodeType},
+    };
+
+    let network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU)?;
+    assert!(network.diff(&network.clone()).is_empty());
+
+    // a single weight mutation
+    let mut child = network.clone();
+    let old_weight = child.edges[4].weight;
+    child.edges[4].weight += 0.5;
+    let edge_id = child.edges[4].id;
+
+    let diff = network.diff(&child);
+    assert_eq!(diff.change_count(), 1);
+    assert_eq!(
+        diff.changed_weights,
+        vec![(edge_id, old_weight, old_weight + 0.5)]
+    );
+
+    // a single bias mutation
+    let mut child = network.clone();
+    let output_node_id = child.output_node_ids()[0];
+    child
+        .nodes
+        .iter_mut()
+        .find(|node| node.id == output_node_id)
+        .unwrap()
+        .bias = -1.0;
+
+    let diff = network.diff(&child);
+    assert_eq!(diff.change_count(), 1);
+    assert_eq!(diff.changed_biases, vec![(output_node_id, 0.0, -1.0)]);
+
+    // structural changes, in both directions
+    let mut child = network.clone();
+    let input_node_id = child.input_node_ids()[0];
+    let layer_id = child.add_layer();
+    let node_id = Node::create(&mut child, layer_id, 0.0)?;
+    let new_edge_id = Edge::create(&mut child, input_node_id, node_id, 1.0)?;
+    let hidden_node_id = network
+        .nodes
+        .iter()
+        .find(|node| node.node_type == NodeType::HiddenNode)
+        .unwrap()
+        .id;
+    child.remove_node(hidden_node_id)?;
+
+    let diff = network.diff(&child);
+    assert_eq!(diff.added_nodes, vec![node_id]);
+    assert_eq!(diff.added_edges, vec![new_edge_id]);
+    assert_eq!(diff.removed_nodes, vec![hidden_node_id]);
+    assert_eq!(diff.removed_edges.len(), 3);
+    assert!(diff.changed_weights.is_empty() && diff.changed_biases.is_empty());
+
+    let reverse = child.diff(&network);
+    assert_eq!(reverse.removed_nodes, diff.added_nodes);
+    assert_eq!(reverse.added_edges, diff.removed_edges);
+
+    Ok(())
+}
+
+#[test]
+fn test_output_range() -> anyhow::Result<()> {
+    use crate::activationfn::ActivationFn;
+
+    assert_eq!(ActivationFn::Tanh.output_range(), (Some(-1.0), Some(1.0)));
+    assert_eq!(
+        ActivationFn::Step(0.5).output_range(),
+        (Some(0.0), Some(1.0))
+    );
+
+    for activation_fn in [
+        ActivationFn::ReLU,
+        ActivationFn::Sigmoid,
+        ActivationFn::Tanh,
+        ActivationFn::Linear,
+        ActivationFn::Step(0.5),
+        ActivationFn::GELU,
+        ActivationFn::ParametricSigmoid(3.0),
+        ActivationFn::ScaledTanh(-2.0),
+    ] {
+        let (lower, upper) = activation_fn.output_range();
+
+        for step in -2000..=2000 {
+            let value = activation_fn.run(step as Float / 100.0);
+
+            if let Some(lower) = lower {
+                assert!(value >= lower);
+            }
+
+            if let Some(upper) = upper {
+                assert!(value <= upper);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_normalizer() -> anyhow::Result<()> {
+    use crate::normalizer::{NormalizeMode, Normalizer};
+
+    let samples = vec![vec![1.0, 5.0], vec![3.0, 5.0], vec![5.0, 5.0]];
+
+    let standard = Normalizer::fit(&samples, NormalizeMode::Standard)?;
+    let transformed = samples
+        .iter()
+        .map(|sample| standard.transform(sample))
+        .collect::<anyhow::Result<Vec<Vec<Float>>>>()?;
+
+    // mean 3 and standard deviation sqrt(8 / 3) in the first dimension; the
+    // second dimension is constant, so it is only shifted
+    let deviation = Float::sqrt(8.0 / 3.0);
+    assert_eq!(transformed[0], vec![-2.0 / deviation, 0.0]);
+    assert_eq!(transformed[1], vec![0.0, 0.0]);
+    assert_eq!(transformed[2], vec![2.0 / deviation, 0.0]);
+
+    for (sample, transformed) in samples.iter().zip(transformed.iter()) {
+        let restored = standard.inverse_transform(transformed)?;
+
+        for (value, restored) in sample.iter().zip(restored) {
+            assert!((value - restored).abs() < 1e-6);
+        }
+    }
+
+    let min_max = Normalizer::fit(&samples, NormalizeMode::MinMax)?;
+    assert_eq!(min_max.transform(&[1.0, 5.0])?, vec![0.0, 0.0]);
+    assert_eq!(min_max.transform(&[5.0, 6.0])?, vec![1.0, 1.0]);
+    assert_eq!(min_max.inverse_transform(&[0.5, 0.0])?, vec![3.0, 5.0]);
+
+    let json = serde_json::to_string(&min_max)?;
+    let loaded: Normalizer = serde_json::from_str(&json)?;
+    assert_eq!(loaded, min_max);
+    assert_eq!(loaded.mode(), NormalizeMode::MinMax);
+
+    assert!(min_max.transform(&[1.0]).is_err());
+    assert!(Normalizer::fit(&[], NormalizeMode::Standard).is_err());
+    assert!(Normalizer::fit(&[vec![1.0], vec![1.0, 2.0]], NormalizeMode::Standard).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_by_id() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+    use std::collections::HashMap;
+
+    let mut network = Network::create(3, 2, ActivationFn::Linear)?;
+    let input_node_ids = network.input_node_ids();
+    let output_node_ids = network.output_node_ids();
+    Edge::create(&mut network, input_node_ids[0], output_node_ids[0], 1.0)?;
+    Edge::create(&mut network, input_node_ids[1], output_node_ids[0], 2.0)?;
+    Edge::create(&mut network, input_node_ids[2], output_node_ids[1], 3.0)?;
+
+    let inputs = HashMap::from([
+        (input_node_ids[2], 1.0),
+        (input_node_ids[0], 0.5),
+        (input_node_ids[1], 0.25),
+    ]);
+    let mut outputs = HashMap::new();
+    network.fire_by_id(&inputs, None, &mut outputs)?;
+
+    let mut positional = vec![];
+    network.fire(vec![0.5, 0.25, 1.0], &mut positional)?;
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[&output_node_ids[0]], positional[0]);
+    assert_eq!(outputs[&output_node_ids[1]], positional[1]);
+
+    // sparse inputs fall back to the default
+    let sparse = HashMap::from([(input_node_ids[1], 1.0)]);
+    network.fire_by_id(&sparse, Some(1.0), &mut outputs)?;
+    assert_eq!(outputs[&output_node_ids[0]], 3.0);
+    assert_eq!(outputs[&output_node_ids[1]], 3.0);
+
+    assert!(network.fire_by_id(&sparse, None, &mut outputs).is_err());
+
+    // outputs are keyed by id, so they are not valid inputs
+    let wrong = HashMap::from([(output_node_ids[0], 1.0)]);
+    assert!(network.fire_by_id(&wrong, Some(0.0), &mut outputs).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_preactivation_clamp() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+
+    let mut network = Network::create(1, 2, ActivationFn::Sigmoid)?;
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_ids = network.output_node_ids();
+    Edge::create(&mut network, input_node_id, output_node_ids[0], 1e30)?;
+    Edge::create(&mut network, input_node_id, output_node_ids[1], -1e30)?;
+
+    // the weighted sums saturate Sigmoid to exactly 1 and 0
+    let mut outputs = vec![];
+    network.fire(vec![1e10], &mut outputs)?;
+    assert_eq!(outputs, vec![1.0, 0.0]);
+
+    network.set_preactivation_clamp((-10.0, 10.0))?;
+    network.fire(vec![1e10], &mut outputs)?;
+    for output in outputs.iter() {
+        assert!(output.is_finite() && *output > 0.0 && *output < 1.0);
+    }
+    assert_eq!(outputs[0], ActivationFn::Sigmoid.run(10.0));
+
+    // clamped nodes do not change with the inputs
+    let jacobian = network.jacobian(&[1e10])?;
+    assert_eq!(jacobian, vec![vec![0.0], vec![0.0]]);
+
+    let mut loaded = Network::deserialized(&network.serialize()?)?;
+    let mut loaded_outputs = vec![];
+    loaded.fire(vec![1e10], &mut loaded_outputs)?;
+    assert_eq!(outputs, loaded_outputs);
+
+    network.clear_preactivation_clamp();
+    network.fire(vec![1e10], &mut outputs)?;
+    assert_eq!(outputs, vec![1.0, 0.0]);
+
+    assert!(network.set_preactivation_clamp((1.0, -1.0)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_checked() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let layer_id = network.add_layer();
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    let first_id = Node::create(&mut network, layer_id, 0.0)?;
+    let second_id = Node::create(&mut network, layer_id, 0.0)?;
+
+    // inf - inf is NaN at the output, but the first bad value is the hidden node
+    Edge::create(&mut network, input_node_id, first_id, Float::MAX)?;
+    Edge::create(&mut network, input_node_id, second_id, Float::MAX)?;
+    Edge::create(&mut network, first_id, output_node_id, 1.0)?;
+    Edge::create(&mut network, second_id, output_node_id, -1.0)?;
+
+    let mut outputs = vec![];
+    network.fire_checked(vec![1.0], &mut outputs)?;
+    assert_eq!(outputs, vec![0.0]);
+
+    let error = network.fire_checked(vec![2.0], &mut outputs).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "Node {} in layer {:?} has a value of inf",
+            first_id, layer_id
+        )
+    );
+
+    // the unchecked version lets the NaN through
+    network.fire(vec![2.0], &mut outputs)?;
+    assert!(outputs[0].is_nan());
+
+    // NaN inputs are caught at the input node
+    let error = network
+        .fire_checked(vec![Float::NAN], &mut outputs)
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with(&format!("Node {} ", input_node_id)));
+
+    // the network can still be fired normally afterwards
+    network.fire_checked(vec![0.5], &mut outputs)?;
+    assert_eq!(outputs, vec![0.0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_trim_empty_layers() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerID, network::Network};
+
+    let mut network = Network::feedforward(&[2, 3, 2, 4, 1], ActivationFn::ReLU)?;
+    network.set_layer_activation(LayerID::HiddenLayer(1), ActivationFn::Tanh)?;
+    network.set_layer_activation(LayerID::HiddenLayer(2), ActivationFn::Sigmoid)?;
+
+    // empty the middle layer
+    for node_id in network.layer_node_ids(LayerID::HiddenLayer(1))? {
+        network.remove_node(node_id)?;
+    }
+    assert_eq!(network.trim_empty_layers(), 1);
+
+    assert_eq!(
+        network.layers,
+        vec![
+            LayerID::InputLayer,
+            LayerID::HiddenLayer(0),
+            LayerID::HiddenLayer(1),
+            LayerID::OutputLayer
+        ]
+    );
+    assert_eq!(network.layer_node_ids(LayerID::HiddenLayer(0))?.len(), 3);
+    assert_eq!(network.layer_node_ids(LayerID::HiddenLayer(1))?.len(), 4);
+
+    // the layer defaults follow their layers
+    assert_eq!(
+        network.layer_activation_fn(LayerID::HiddenLayer(1)),
+        ActivationFn::Sigmoid
+    );
+
+    network.verify_feedforward_invariant()?;
+    let mut outputs = vec![];
+    network.fire(vec![0.5, -0.25], &mut outputs)?;
+    assert_eq!(outputs.len(), 1);
+
+    assert_eq!(network.trim_empty_layers(), 0);
+    assert_eq!(network.add_layer(), LayerID::HiddenLayer(2));
+
+    Ok(())
+}
+
+#[test]
+fn test_output_transform() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+
+    let mut network = Network::create(2, 2, ActivationFn::Linear)?;
+    let input_node_ids = network.input_node_ids();
+    let output_node_ids = network.output_node_ids();
+    Edge::create(&mut network, input_node_ids[0], output_node_ids[0], 1.0)?;
+    Edge::create(&mut network, input_node_ids[1], output_node_ids[1], 2.0)?;
+
+    network.set_output_transform(vec![2.0, -1.0], vec![1.0, 0.5])?;
+
+    let mut outputs = vec![];
+    network.fire(vec![1.0, 3.0], &mut outputs)?;
+    assert_eq!(outputs, vec![3.0, -5.5]);
+
+    let mut into = [0.0; 2];
+    network.fire_into(&[1.0, 3.0], &mut into)?;
+    assert_eq!(into.to_vec(), outputs);
+
+    assert_eq!(
+        network.jacobian(&[1.0, 3.0])?,
+        vec![vec![2.0, 0.0], vec![0.0, -2.0]]
+    );
+
+    let mut loaded = Network::deserialized(&network.serialize()?)?;
+    let mut loaded_outputs = vec![];
+    loaded.fire(vec![1.0, 3.0], &mut loaded_outputs)?;
+    assert_eq!(loaded_outputs, outputs);
+
+    network.clear_output_transform();
+    network.fire(vec![1.0, 3.0], &mut outputs)?;
+    assert_eq!(outputs, vec![1.0, 6.0]);
+
+    assert!(network
+        .set_output_transform(vec![1.0], vec![0.0, 0.0])
+        .is_err());
+    assert!(network
+        .set_output_transform(vec![1.0, 1.0], vec![0.0])
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_is_fully_connected() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let network = Network::feedforward(&[2, 3, 2], ActivationFn::ReLU)?;
+    assert!(network.is_fully_connected());
+
+    let mut network = Network::create(2, 2, ActivationFn::ReLU)?;
+    let layer_id = network.add_layer();
+    let input_node_ids = network.input_node_ids();
+    let output_node_ids = network.output_node_ids();
+    let hidden_node_id = Node::create(&mut network, layer_id, 0.0)?;
+    let bias_node_id = network.add_bias_node(layer_id)?;
+    assert!(!network.is_fully_connected());
+
+    // the first output is reached through the hidden node
+    Edge::create(&mut network, input_node_ids[1], hidden_node_id, 1.0)?;
+    Edge::create(&mut network, hidden_node_id, output_node_ids[0], 1.0)?;
+
+    // a bias node is not an input, and recurrent edges do not count
+    Edge::create(&mut network, bias_node_id, output_node_ids[1], 1.0)?;
+    Edge::create_recurrent(&mut network, output_node_ids[0], output_node_ids[1], 1.0)?;
+    assert!(!network.is_fully_connected());
+
+    let edge_id = Edge::create(&mut network, hidden_node_id, output_node_ids[1], 1.0)?;
+    assert!(network.is_fully_connected());
+
+    network.set_edge_enabled(edge_id, false)?;
+    assert!(!network.is_fully_connected());
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_mc_dropout() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // two hidden nodes, each carrying 1 to the output
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let layer_id = network.add_layer();
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+
+    for _ in 0..2 {
+        let hidden_node_id = Node::create(&mut network, layer_id, 0.0)?;
+        Edge::create(&mut network, input_node_id, hidden_node_id, 1.0)?;
+        Edge::create(&mut network, hidden_node_id, output_node_id, 1.0)?;
+    }
+
+    let mut rng = StdRng::seed_from_u64(0);
+
+    // without dropout every run matches fire
+    let runs = network.fire_mc_dropout(vec![1.0], 5, 0.0, &mut rng)?;
+    assert_eq!(runs, vec![vec![2.0]; 5]);
+
+    // with half the nodes dropped and the rest doubled, each run is 0, 2 or 4
+    let runs = network.fire_mc_dropout(vec![1.0], 2000, 0.5, &mut rng)?;
+    assert_eq!(runs.len(), 2000);
+    assert!(runs.iter().all(|run| [0.0, 2.0, 4.0].contains(&run[0])));
+    for value in [0.0, 2.0, 4.0] {
+        assert!(runs.iter().any(|run| run[0] == value));
+    }
+
+    let mean = runs.iter().map(|run| run[0]).sum::<Float>() / runs.len() as Float;
+    assert!((mean - 2.0).abs() < 0.2);
+
+    // dropout does not leak into normal firing
+    let mut outputs = vec![];
+    network.fire(vec![1.0], &mut outputs)?;
+    assert_eq!(outputs, vec![2.0]);
+
+    assert!(network
+        .fire_mc_dropout(vec![1.0], 1, 1.0, &mut rng)
+        .is_err());
+    assert!(network
+        .fire_mc_dropout(vec![1.0, 2.0], 1, 0.5, &mut rng)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_edge_queries() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network, node::Node};
+
+    let mut network = Network::create(1, 1, ActivationFn::ReLU)?;
+    let layer_id = network.add_layer();
+    let input_node_id = network.input_node_ids()[0];
+    let hidden_node_id = Node::create(&mut network, layer_id, 0.2)?;
+    let output_node_id = network.output_node_ids()[0];
+
+    let input_to_hidden = Edge::create(&mut network, input_node_id, hidden_node_id, 1.3)?;
+    let hidden_to_output = Edge::create(&mut network, hidden_node_id, output_node_id, 1.5)?;
+    let input_to_output = Edge::create(&mut network, input_node_id, output_node_id, 2.0)?;
+    let recurrent = Edge::create_recurrent(&mut network, output_node_id, hidden_node_id, 0.5)?;
+
+    assert!(network.incoming_edges(input_node_id).is_empty());
+    assert_eq!(
+        network.outgoing_edges(input_node_id),
+        vec![input_to_hidden, input_to_output]
+    );
+    assert_eq!(
+        network.incoming_edges(hidden_node_id),
+        vec![input_to_hidden, recurrent]
+    );
+    assert_eq!(
+        network.outgoing_edges(hidden_node_id),
+        vec![hidden_to_output]
+    );
+    assert_eq!(
+        network.incoming_edges(output_node_id),
+        vec![hidden_to_output, input_to_output]
+    );
+    assert_eq!(network.outgoing_edges(output_node_id), vec![recurrent]);
+
+    assert_eq!(
+        network.edge_endpoints(recurrent),
+        Some((output_node_id, hidden_node_id))
+    );
+    assert_eq!(network.edge_endpoints(1000), None);
+    assert!(network.incoming_edges(1000).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_input_activation() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn,
+        edge::Edge,
+        layer::LayerID,
+        network::{Network, FORMAT_VERSION},
+        node::Node,
+    };
+
+    // inputs are linear by default, even when the network is not
+    let mut network = Network::create(1, 1, ActivationFn::Sigmoid)?;
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    Edge::create(&mut network, input_node_id, output_node_id, 1.0)?;
+    assert_eq!(
+        network.get_node(input_node_id).unwrap().activation_fn,
+        ActivationFn::Linear
+    );
+
+    let values = network.fire_and_capture(vec![3.0])?;
+    assert_eq!(values[&input_node_id], 3.0);
+
+    // squash the inputs through tanh
+    network.set_layer_activation(LayerID::InputLayer, ActivationFn::Tanh)?;
+    let values = network.fire_and_capture(vec![3.0])?;
+    assert_eq!(values[&input_node_id], Float::tanh(3.0));
+    assert_eq!(
+        values[&output_node_id],
+        ActivationFn::Sigmoid.run(Float::tanh(3.0))
+    );
+
+    network.set_input(input_node_id, 3.0)?;
+    let mut outputs = vec![];
+    network.fire_current(&mut outputs)?;
+    assert_eq!(outputs, vec![ActivationFn::Sigmoid.run(Float::tanh(3.0))]);
+
+    let jacobian = network.jacobian(&[0.5])?;
+    let output = ActivationFn::Sigmoid.run(Float::tanh(0.5));
+    let expected = output * (1.0 - output) * (1.0 - Float::tanh(0.5).powi(2));
+    assert!((jacobian[0][0] - expected).abs() < 1e-6);
+
+    // the input activation survives a round trip through serde directly as
+    // well as through `serialize`
+    let from_serde: Network = serde_json::from_str(&serde_json::to_string(&network)?)?;
+    let from_string = Network::deserialized(&network.serialize()?)?;
+    let mixed = Network::deserialized(&serde_json::to_string(&network)?)?;
+    let mixed_back: Network = serde_json::from_str(&network.serialize()?)?;
+
+    for mut loaded in [from_serde, from_string, mixed, mixed_back] {
+        loaded.fire(vec![3.0], &mut outputs)?;
+        assert_eq!(outputs, vec![ActivationFn::Sigmoid.run(Float::tanh(3.0))]);
+    }
+
+    // a single input node with its own activation
+    let extra_id = Node::create_with_custom_activation(
+        &mut network,
+        LayerID::InputLayer,
+        0.0,
+        ActivationFn::ReLU,
+    )?;
+    Edge::create(&mut network, extra_id, output_node_id, 1.0)?;
+    network.fire(vec![0.0, -2.0], &mut outputs)?;
+    assert_eq!(outputs, vec![0.5]);
+
+    // networks saved before version 2 ignored input activations
+    let mut value: serde_json::Value = serde_json::from_str(&network.serialize()?)?;
+    value["version"] = serde_json::Value::from(1);
+    let mut old = Network::deserialized(&value.to_string())?;
+    old.fire(vec![3.0, -2.0], &mut outputs)?;
+    assert_eq!(outputs, vec![ActivationFn::Sigmoid.run(1.0)]);
+
+    let mut old_from_serde: Network = serde_json::from_value(value)?;
+    old_from_serde.fire(vec![3.0, -2.0], &mut outputs)?;
+    assert_eq!(outputs, vec![ActivationFn::Sigmoid.run(1.0)]);
+
+    let new_input_id = Node::create(&mut old, LayerID::InputLayer, 0.0)?;
+    assert_eq!(
+        old.get_node(new_input_id).unwrap().activation_fn,
+        ActivationFn::Linear
+    );
+    assert!(old
+        .serialize()?
+        .contains(&format!("\"version\":{}", FORMAT_VERSION)));
+
+    Ok(())
+}
+
+#[test]
+fn test_network_builder() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn,
+        builder::NetworkBuilder,
+        layer::{LayerID, LayerSpec},
+        network::Network,
+        weightinit::WeightInit,
+    };
+
+    let mut built = NetworkBuilder::new()
+        .inputs(3)
+        .dense(8, ActivationFn::ReLU)
+        .dense(4, ActivationFn::Tanh)
+        .outputs(2, ActivationFn::Sigmoid)
+        .build()?;
+
+    let mut manual = Network::create(3, 2, ActivationFn::Sigmoid)?;
+    manual.set_layer_activation(LayerID::OutputLayer, ActivationFn::Sigmoid)?;
+    manual.push_dense(LayerSpec {
+        size: 8,
+        activation: ActivationFn::ReLU,
+    })?;
+    let last_layer = manual.push_dense(LayerSpec {
+        size: 4,
+        activation: ActivationFn::Tanh,
+    })?;
+    manual.connect_layers(last_layer, LayerID::OutputLayer, WeightInit::Uniform(1.0))?;
+
+    // with the same parameters, the two networks are identical
+    manual.import_parameters(&built.export_parameters())?;
+    assert_eq!(built.serialize()?, manual.serialize()?);
+    assert_eq!(built.edges.len(), 3 * 8 + 8 * 4 + 4 * 2);
+
+    let mut built_outputs = vec![];
+    let mut manual_outputs = vec![];
+    built.fire(vec![0.1, -0.4, 0.9], &mut built_outputs)?;
+    manual.fire(vec![0.1, -0.4, 0.9], &mut manual_outputs)?;
+    assert_eq!(built_outputs, manual_outputs);
+
+    // without hidden layers the inputs connect straight to the outputs
+    let network = NetworkBuilder::new()
+        .inputs(2)
+        .outputs(1, ActivationFn::Linear)
+        .build()?;
+    assert_eq!(network.edges.len(), 2);
+
+    assert!(NetworkBuilder::new().inputs(2).build().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_replace_activation() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn,
+        layer::LayerID,
+        network::Network,
+        node::{Node, NodeType},
+    };
+
+    let activations = |network: &Network, node_type: NodeType| {
+        network
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == node_type)
+            .map(|node| node.activation_fn)
+            .collect::<Vec<ActivationFn>>()
+    };
+
+    let mut network = Network::feedforward(&[2, 3, 2], ActivationFn::Step(0.5))?;
+    let layer_id = network.add_layer();
+    Node::create_with_custom_activation(&mut network, layer_id, 0.0, ActivationFn::Step(0.0))?;
+
+    // Step only matches with the same threshold
+    assert_eq!(
+        network.replace_activation(ActivationFn::Step(0.5), ActivationFn::ReLU),
+        5
+    );
+    assert_eq!(
+        activations(&network, NodeType::HiddenNode),
+        vec![
+            ActivationFn::ReLU,
+            ActivationFn::ReLU,
+            ActivationFn::ReLU,
+            ActivationFn::Step(0.0)
+        ]
+    );
+    assert_eq!(
+        activations(&network, NodeType::InputNode),
+        vec![ActivationFn::Linear; 2]
+    );
+
+    // new nodes follow the replaced defaults
+    let node_id = Node::create(&mut network, LayerID::HiddenLayer(0), 0.0)?;
+    assert_eq!(
+        network.get_node(node_id).unwrap().activation_fn,
+        ActivationFn::ReLU
+    );
+
+    assert_eq!(
+        network.replace_activation(ActivationFn::Sigmoid, ActivationFn::Tanh),
+        0
+    );
+
+    network.set_all_activations(ActivationFn::GELU);
+    assert!(activations(&network, NodeType::HiddenNode)
+        .iter()
+        .all(|activation_fn| *activation_fn == ActivationFn::GELU));
+    assert_eq!(
+        activations(&network, NodeType::OutputNode),
+        vec![ActivationFn::GELU; 2]
+    );
+    assert_eq!(
+        activations(&network, NodeType::InputNode),
+        vec![ActivationFn::Linear; 2]
+    );
+
+    let node_id = Node::create(&mut network, layer_id, 0.0)?;
+    assert_eq!(
+        network.get_node(node_id).unwrap().activation_fn,
+        ActivationFn::GELU
+    );
+    let node_id = Node::create(&mut network, LayerID::InputLayer, 0.0)?;
+    assert_eq!(
+        network.get_node(node_id).unwrap().activation_fn,
+        ActivationFn::Linear
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_serialize_canonical() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerID, network::Network};
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    let mut network = Network::feedforward(&[3, 4, 4, 2], ActivationFn::ReLU)?;
+    network.set_layer_activation(LayerID::OutputLayer, ActivationFn::Sigmoid)?;
+    network.set_fitness(0.5);
+    let canonical = network.serialize_canonical()?;
+
+    let mut outputs = vec![];
+    network.fire(vec![0.2, -0.3, 0.7], &mut outputs)?;
+
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..10 {
+        let mut shuffled = network.clone();
+        shuffled.nodes.shuffle(&mut rng);
+        shuffled.edges.shuffle(&mut rng);
+        shuffled.layer_activation_fns.shuffle(&mut rng);
+        shuffled.invalidate_cache();
+
+        assert_eq!(shuffled.serialize_canonical()?, canonical);
+
+        let mut shuffled_outputs = vec![];
+        shuffled.fire(vec![0.2, -0.3, 0.7], &mut shuffled_outputs)?;
+        assert_eq!(shuffled_outputs, outputs);
+    }
+
+    let mut loaded = Network::deserialized(&canonical)?;
+    let mut loaded_outputs = vec![];
+    loaded.fire(vec![0.2, -0.3, 0.7], &mut loaded_outputs)?;
+    for (loaded_output, output) in loaded_outputs.iter().zip(outputs.iter()) {
+        assert!((loaded_output - output).abs() < 1e-6);
+    }
+    assert_eq!(loaded.fitness(), Some(0.5));
+
+    Ok(())
+}
+
+#[test]
+fn test_flops() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+
+    let mut network = test_creation()?;
+    assert_eq!(network.flops(), 3 + 2);
+
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    let bias_node_id = network.add_bias_node(crate::layer::LayerID::InputLayer)?;
+    Edge::create(&mut network, bias_node_id, output_node_id, 0.1)?;
+    Edge::create_recurrent(&mut network, output_node_id, input_node_id, 0.5)?;
+    assert_eq!(network.flops(), 4 + 2);
+
+    let edge_id = network.incoming_edges(output_node_id)[0];
+    network.set_edge_enabled(edge_id, false)?;
+    assert_eq!(network.flops(), 3 + 2);
+
+    assert_eq!(Network::create(4, 2, ActivationFn::ReLU)?.flops(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_fire_without_edges() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, network::Network};
+
+    // with no edges, each output is its activation function applied to its
+    // bias, whatever the inputs are
+    let mut network = Network::create(2, 2, ActivationFn::Sigmoid)?;
+    let output_node_ids = network.output_node_ids();
+    network.get_node_mut(output_node_ids[1]).unwrap().bias = 2.0;
+    assert!(!network.is_fully_connected());
+
+    let mut output = vec![];
+    network.fire(vec![0.3, -4.0], &mut output)?;
+    assert_eq!(output, vec![0.5, ActivationFn::Sigmoid.run(2.0)]);
+
+    let mut other_output = vec![];
+    network.fire(vec![10.0, 10.0], &mut other_output)?;
+    assert_eq!(other_output, output);
+
+    // strict mode names the first disconnected output
+    network.set_strict(true);
+    let error = network.fire(vec![0.3, -4.0], &mut output).unwrap_err();
+    assert!(error.to_string().contains(&output_node_ids[0].to_string()));
+
+    // every way of firing is covered
+    assert!(network.fire_checked(vec![0.3, -4.0], &mut output).is_err());
+    assert!(network.fire_current(&mut output).is_err());
+    assert!(network.fire_and_capture(vec![0.3, -4.0]).is_err());
+    assert!(network.fire_sequence(&[vec![0.3, -4.0]]).is_err());
+    assert!(network.jacobian(&[0.3, -4.0]).is_err());
+
+    let input_node_id = network.input_node_ids()[0];
+    Edge::create(&mut network, input_node_id, output_node_ids[0], 1.0)?;
+    let error = network.fire(vec![0.3, -4.0], &mut output).unwrap_err();
+    assert!(error.to_string().contains(&output_node_ids[1].to_string()));
+
+    Edge::create(&mut network, input_node_id, output_node_ids[1], 1.0)?;
+    network.fire(vec![0.3, -4.0], &mut output)?;
+    network.fire_checked(vec![0.3, -4.0], &mut output)?;
+    network.fire_current(&mut output)?;
+    network.jacobian(&[0.3, -4.0])?;
+
+    // disabling an edge can disconnect an output again
+    let edge_id = network.incoming_edges(output_node_ids[1])[0];
+    network.set_edge_enabled(edge_id, false)?;
+    assert!(network.fire(vec![0.3, -4.0], &mut output).is_err());
+    network.set_edge_enabled(edge_id, true)?;
+    network.fire(vec![0.3, -4.0], &mut output)?;
+
+    // strict mode is saved with the network
+    let loaded = Network::deserialized(&network.serialize()?)?;
+    assert!(loaded.is_strict());
+
+    Ok(())
+}
+
+#[test]
+fn test_chain() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network};
+
+    let mut first = Network::feedforward(&[3, 4, 2], ActivationFn::ReLU)?;
+    first.set_layer_activation(LayerID::OutputLayer, ActivationFn::Tanh)?;
+    let mut second = Network::feedforward(&[2, 3, 2], ActivationFn::Sigmoid)?;
+    second.set_layer_activation(LayerID::InputLayer, ActivationFn::ReLU)?;
+
+    let mut chained = Network::chain(first.clone(), second.clone())?;
+    assert_eq!(chained.input_size(), 3);
+    assert_eq!(chained.output_size(), 2);
+    assert_eq!(chained.depth(), first.depth() + 1 + second.depth());
+    assert_eq!(
+        chained.parameter_count().weights,
+        first.parameter_count().weights + second.parameter_count().weights + 2
+    );
+    assert!(chained.is_fully_connected());
+    chained.verify_feedforward_invariant()?;
+
+    for inputs in [vec![0.1, -0.5, 0.9], vec![1.0, 2.0, -3.0]] {
+        let mut hidden = vec![];
+        let mut expected = vec![];
+        let mut output = vec![];
+
+        first.fire(inputs.clone(), &mut hidden)?;
+        second.fire(hidden, &mut expected)?;
+        chained.fire(inputs, &mut output)?;
+
+        for (output, expected) in output.iter().zip(expected.iter()) {
+            assert!((output - expected).abs() < 1e-6);
+        }
+    }
+
+    // the first network's outputs must line up with the second's inputs
+    let wide = Network::feedforward(&[3, 3], ActivationFn::ReLU)?;
+    assert!(Network::chain(wide, second.clone()).is_err());
+
+    let classifier = Network::create_classifier(3, 2, ActivationFn::ReLU)?;
+    assert!(Network::chain(classifier, second).is_err());
+
+    // the second network's inputs are clamped once they are hidden nodes
+    let mut first = Network::create(1, 1, ActivationFn::ScaledTanh(4.0))?;
+    Edge::create(&mut first, 1, 2, 1.0)?;
+    first.set_preactivation_clamp((-1.0, 1.0))?;
+    let mut second = Network::create(1, 1, ActivationFn::Linear)?;
+    Edge::create(&mut second, 1, 2, 0.25)?;
+    second.set_preactivation_clamp((-1.0, 1.0))?;
+    let mut chained = Network::chain(first.clone(), second.clone())?;
+
+    let mut hidden = vec![];
+    let mut expected = vec![];
+    let mut output = vec![];
+
+    first.fire(vec![1.0], &mut hidden)?;
+    assert!(hidden[0] > 1.0);
+    second.fire(hidden.clone(), &mut expected)?;
+    assert_eq!(expected, vec![hidden[0] * 0.25]);
+
+    chained.fire(vec![1.0], &mut output)?;
+    assert_eq!(output, vec![0.25]);
+
+    Ok(())
+}
+
+#[test]
+fn test_layer_weights() -> anyhow::Result<()> {
+    use crate::{activationfn::ActivationFn, layer::LayerID, network::Network};
+
+    let mut network = Network::feedforward(&[3, 4, 2], ActivationFn::ReLU)?;
+    network.map_biases(|_| 0.1);
+    let hidden = LayerID::HiddenLayer(0);
+
+    let w1 = network.layer_weights(LayerID::InputLayer, hidden)?;
+    let b1 = network.layer_biases(hidden)?;
+    let w2 = network.layer_weights(hidden, LayerID::OutputLayer)?;
+    let b2 = network.layer_biases(LayerID::OutputLayer)?;
+    assert_eq!((w1.len(), w1[0].len(), b1.len()), (4, 3, 4));
+    assert_eq!((w2.len(), w2[0].len(), b2.len()), (2, 4, 2));
+
+    // no edges skip the hidden layer
+    let skip = network.layer_weights(LayerID::InputLayer, LayerID::OutputLayer)?;
+    assert!(skip.iter().flatten().all(|weight| *weight == 0.0));
+
+    let dense = |weights: &[Vec<Float>], biases: &[Float], x: &[Float]| {
+        weights
+            .iter()
+            .zip(biases)
+            .map(|(row, bias)| {
+                let sum = row.iter().zip(x).map(|(w, x)| w * x).sum::<Float>() + bias;
+                sum.max(0.0)
+            })
+            .collect::<Vec<Float>>()
+    };
+
+    let inputs = vec![0.5, -0.2, 0.9];
+    let expected = dense(&w2, &b2, &dense(&w1, &b1, &inputs));
+
+    let mut output = vec![];
+    network.fire(inputs, &mut output)?;
+
+    for (output, expected) in output.iter().zip(expected.iter()) {
+        assert!((output - expected).abs() < 1e-6);
+    }
+
+    assert!(network
+        .layer_weights(LayerID::HiddenLayer(1), hidden)
+        .is_err());
+    assert!(network.layer_biases(LayerID::HiddenLayer(1)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_failed_fire_resets_nodes() -> anyhow::Result<()> {
+    use crate::{
+        activationfn::ActivationFn, edge::Edge, layer::LayerID, network::Network, node::Node,
+    };
+
+    let mut network = Network::create(1, 1, ActivationFn::Linear)?;
+    let input_node_id = network.input_node_ids()[0];
+    let output_node_id = network.output_node_ids()[0];
+    Edge::create(&mut network, input_node_id, output_node_id, 1.0)?;
+
+    // a transform for one output fails to apply once there are two
+    network.set_output_transform(vec![1.0], vec![0.0])?;
+    Node::create(&mut network, LayerID::OutputLayer, 0.0)?;
+
+    let mut output = vec![];
+    assert!(network.fire(vec![2.0], &mut output).is_err());
+    assert!(network.fire_checked(vec![2.0], &mut output).is_err());
+    assert!(network.fire_sequence(&[vec![2.0], vec![2.0]]).is_err());
+    assert!(network.fire_current(&mut output).is_err());
+
+    network.clear_output_transform();
+
+    // the failed fires left nothing to add to
+    network.fire(vec![2.0], &mut output)?;
+    assert_eq!(output, vec![2.0, 0.0]);
+
+    network.fire_current(&mut output)?;
+    assert_eq!(output, vec![0.0, 0.0]);
+
+    Ok(())
+}
+
 // #[test]
 // fn test_neat() -> anyhow::Result<()> {
 //     use crate::{
@@ -1,21 +1,307 @@
 use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Write as FmtWrite},
     fs::File,
     io::{Read, Write},
     process::{ExitCode, Termination},
 };
 
-use crate::{activationfn::ActivationFn, edge::Edge, layer::LayerID, node::Node};
-use anyhow::{ensure, Context, Result};
-use serde::{Deserialize, Serialize};
+use crate::{
+    activationfn::ActivationFn,
+    cache::FireCache,
+    edge::Edge,
+    layer::{LayerID, LayerSpec},
+    node::{Node, NodeType},
+    quantized::QuantizedNetwork,
+    weightinit::WeightInit,
+    Float,
+};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use rand::Rng;
+use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// The version of the serialized network format. Bump this, and add a step
+/// to `migrate`, whenever a change to the format cannot be covered by
+/// `#[serde(default)]` alone.
+pub(crate) const FORMAT_VERSION: u64 = 2;
+
+/// The serialized form of a network: its fields, plus the format version.
+#[derive(Serialize)]
+struct Versioned<'a> {
+    version: u64,
+    #[serde(flatten)]
+    network: Fields<'a>,
+}
+
+/// Serializes a network's fields, without the version.
+struct Fields<'a>(&'a Network);
+
+impl Serialize for Fields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        NetworkFields::serialize(self.0, serializer)
+    }
+}
+
+/// Upgrades a serialized network from `version` to `FORMAT_VERSION`.
+fn migrate(value: &mut Value, version: u64) -> Result<()> {
+    ensure!(
+        version <= FORMAT_VERSION,
+        "Network was saved with format version {}, but only versions up to {} are supported",
+        version,
+        FORMAT_VERSION
+    );
+
+    // 0 -> 1: networks saved before versioning only lack fields that have
+    // defaults, so there is nothing to change
+
+    // 1 -> 2: input nodes used to ignore their activation function, so make
+    // them linear to keep old networks firing the same
+    if version < 2 {
+        if let Some(nodes) = value.get_mut("nodes").and_then(Value::as_array_mut) {
+            for node in nodes.iter_mut() {
+                if node["node_type"] == "InputNode" {
+                    node["activation_fn"] = Value::from("Linear");
+                }
+            }
+        }
+
+        if let Some(object) = value.as_object_mut() {
+            let layer_activation_fns = object
+                .entry("layer_activation_fns")
+                .or_insert_with(|| Value::Array(vec![]));
+
+            if let Some(layer_activation_fns) = layer_activation_fns.as_array_mut() {
+                layer_activation_fns
+                    .retain(|layer_activation_fn| layer_activation_fn[0] != "InputLayer");
+                layer_activation_fns.push(Value::from(vec!["InputLayer", "Linear"]));
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), Value::from(FORMAT_VERSION));
+    }
+
+    Ok(())
+}
 
 /// A neural network. Interact with this struct to create and modify your network.
-#[derive(Clone, Serialize, Deserialize, Debug)]
+///
+/// `Network` implements `Serialize` and `Deserialize` itself, so the format
+/// version is written and older networks are migrated whether it is saved
+/// with `serialize` or with serde directly. Deserializing goes through
+/// `serde_json::Value`, so it needs a self-describing format.
+#[derive(Clone, Debug)]
 pub struct Network {
     pub(crate) nodes: Vec<Node>,
     pub(crate) edges: Vec<Edge>,
     pub(crate) layers: Vec<LayerID>,
     pub(crate) fitness: Option<f64>,
     pub(crate) activation_fn: ActivationFn,
+    pub(crate) layer_activation_fns: Vec<(LayerID, ActivationFn)>,
+    pub(crate) next_node_id: usize,
+    pub(crate) next_edge_id: usize,
+    pub(crate) softmax_output: bool,
+    pub(crate) preactivation_clamp: Option<(Float, Float)>,
+    pub(crate) output_transform: Option<(Vec<Float>, Vec<Float>)>,
+    pub(crate) strict: bool,
+    pub(crate) cache: Option<FireCache>,
+}
+
+/// The fields of `Network` as they are serialized, without the version. The
+/// compiler checks that every field of `Network` is listed here.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Network")]
+struct NetworkFields {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    layers: Vec<LayerID>,
+    fitness: Option<f64>,
+    activation_fn: ActivationFn,
+    #[serde(default)]
+    layer_activation_fns: Vec<(LayerID, ActivationFn)>,
+    #[serde(default)]
+    next_node_id: usize,
+    #[serde(default)]
+    next_edge_id: usize,
+    #[serde(default)]
+    softmax_output: bool,
+    #[serde(default)]
+    preactivation_clamp: Option<(Float, Float)>,
+    #[serde(default)]
+    output_transform: Option<(Vec<Float>, Vec<Float>)>,
+    #[serde(default)]
+    strict: bool,
+    #[serde(skip)]
+    cache: Option<FireCache>,
+}
+
+impl Serialize for Network {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        if let Some(node) = self
+            .nodes
+            .iter()
+            .find(|node| node.activation_closure.is_some())
+        {
+            return Err(S::Error::custom(format!(
+                "Node {} has an activation closure, which cannot be serialized",
+                node.id
+            )));
+        }
+
+        Versioned {
+            version: FORMAT_VERSION,
+            network: Fields(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Network {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let mut value = Value::deserialize(deserializer)?;
+
+        // networks saved before versioning have no version field
+        let version = match value.get("version") {
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| D::Error::custom("Network version is not a number"))?,
+            None => 0,
+        };
+
+        migrate(&mut value, version).map_err(D::Error::custom)?;
+
+        NetworkFields::deserialize(value).map_err(D::Error::custom)
+    }
+}
+
+/// The result of evaluating a network as a classifier. See
+/// `Network::evaluate_classification`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassificationReport {
+    /// The fraction of samples whose predicted class matched the label.
+    pub accuracy: f64,
+
+    /// `confusion_matrix[label][predicted]` is the number of samples with
+    /// that label that were classified as `predicted`.
+    pub confusion_matrix: Vec<Vec<usize>>,
+}
+
+/// The number of trainable parameters in a network, returned by
+/// `Network::parameter_count`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParamCounts {
+    /// One weight per edge.
+    pub weights: usize,
+
+    /// One bias per hidden and output node.
+    pub biases: usize,
+}
+
+impl ParamCounts {
+    /// The total number of trainable parameters.
+    pub fn total(&self) -> usize {
+        self.weights + self.biases
+    }
+}
+
+/// Statistics of a node's value over a batch of inputs, returned by
+/// `Network::activation_stats`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NodeActivationStats {
+    /// The mean value.
+    pub mean: Float,
+
+    /// The population variance of the value.
+    pub variance: Float,
+
+    /// The fraction of inputs for which the value was nonzero. A hidden node
+    /// with a fraction of 0 is dead.
+    pub fraction_active: Float,
+}
+
+/// The structural differences between two networks, returned by
+/// `Network::diff`. Nodes and edges are matched by id, and every list is
+/// sorted by id.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NetworkDiff {
+    /// Ids of nodes only in the other network.
+    pub added_nodes: Vec<usize>,
+
+    /// Ids of nodes only in this network.
+    pub removed_nodes: Vec<usize>,
+
+    /// Ids of edges only in the other network.
+    pub added_edges: Vec<usize>,
+
+    /// Ids of edges only in this network.
+    pub removed_edges: Vec<usize>,
+
+    /// `(edge id, old weight, new weight)` for edges in both networks whose
+    /// weight changed.
+    pub changed_weights: Vec<(usize, Float, Float)>,
+
+    /// `(node id, old bias, new bias)` for nodes in both networks whose bias
+    /// changed.
+    pub changed_biases: Vec<(usize, Float, Float)>,
+}
+
+impl NetworkDiff {
+    /// The total number of differences.
+    pub fn change_count(&self) -> usize {
+        self.added_nodes.len()
+            + self.removed_nodes.len()
+            + self.added_edges.len()
+            + self.removed_edges.len()
+            + self.changed_weights.len()
+            + self.changed_biases.len()
+    }
+
+    /// Whether the networks have the same nodes, edges, weights and biases.
+    pub fn is_empty(&self) -> bool {
+        self.change_count() == 0
+    }
+}
+
+/// The result of `Network::sparsify` and `Network::skeletonize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SparsifyReport {
+    /// The number of edges removed, including the edges of removed nodes.
+    pub edges_removed: usize,
+
+    /// The number of hidden or bias nodes removed because they could no
+    /// longer affect the outputs.
+    pub nodes_removed: usize,
+
+    /// The number of output nodes still reachable from an input node.
+    pub connected_outputs: usize,
+}
+
+/// `exp(value / temperature)` for each value, normalized to sum to 1. The
+/// largest value is subtracted first so the exponentials cannot overflow.
+pub(crate) fn softmax(values: &[Float], temperature: Float) -> Vec<Float> {
+    let max = values.iter().copied().fold(Float::NEG_INFINITY, Float::max);
+    let exps = values
+        .iter()
+        .map(|value| ((value - max) / temperature).exp())
+        .collect::<Vec<Float>>();
+    let sum = exps.iter().sum::<Float>();
+
+    exps.iter().map(|exp| exp / sum).collect()
+}
+
+/// Index of the largest value, preferring the first on ties.
+pub(crate) fn argmax(values: &[Float]) -> usize {
+    let mut best = 0;
+
+    for (index, value) in values.iter().enumerate() {
+        if *value > values[best] {
+            best = index;
+        }
+    }
+
+    best
 }
 
 impl Network {
@@ -23,6 +309,20 @@ impl Network {
         self.nodes.iter().find(|node| node.id == node_id)
     }
 
+    /// The id the next node will get. Ids only ever increase, so the id of a
+    /// removed node is never handed out again. Networks saved before the
+    /// counter existed continue after their highest id.
+    pub(crate) fn new_node_id(&self) -> usize {
+        let after_max = self.nodes.iter().map(|n| n.id).max().unwrap_or(0) + 1;
+        self.next_node_id.max(after_max)
+    }
+
+    /// The id the next edge will get. See `new_node_id`.
+    pub(crate) fn new_edge_id(&self) -> usize {
+        let after_max = self.edges.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+        self.next_edge_id.max(after_max)
+    }
+
     pub(crate) fn get_node_mut(&mut self, node_id: usize) -> Option<&mut Node> {
         self.nodes.iter_mut().find(|node| node.id == node_id)
     }
@@ -59,160 +359,217 @@ impl Network {
         self.edges.iter().find(|edge| edge.id == edge_id)
     }
 
-    /// Runs the inputs of the network.
+    pub(crate) fn layer_activation_fn(&self, layer_id: LayerID) -> ActivationFn {
+        self.layer_activation_fns
+            .iter()
+            .find(|(id, _)| *id == layer_id)
+            .map(|(_, activation_fn)| *activation_fn)
+            .unwrap_or(self.activation_fn)
+    }
+
+    /// Node ids in an order where every (non-recurrent) edge points forwards.
+    pub(crate) fn topological_node_ids(&self) -> Vec<usize> {
+        let mut nodes = self.nodes.iter().collect::<Vec<&Node>>();
+        nodes.sort_by_key(|node| (node.layer_id, node.id));
+        nodes.iter().map(|node| node.id).collect()
+    }
+
+    /// Sets the activation function of every node in a layer. Nodes created in
+    /// this layer afterwards with `Node::create` will also use it.
     ///
     /// ### Example
     /// ```
-    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
-    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
-    /// # let layerid = network.add_layer();
-    /// # let input_node_id = network.input_node_ids().pop().unwrap();
-    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
-    /// # let output_node_id = network.output_node_ids().pop().unwrap();
-    /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
-    /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
-    /// let mut output = vec![];
-    /// network.fire(vec![0.8], &mut output).unwrap();
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let layer_id = network.add_layer();
+    ///
+    /// network.set_layer_activation(layer_id, ActivationFn::ReLU).unwrap();
+    /// network.set_layer_activation(LayerID::OutputLayer, ActivationFn::Sigmoid).unwrap();
+    ///
+    /// let hidden_node_id = Node::create(&mut network, layer_id, 0.0).unwrap();
     /// ```
-    pub fn fire(&mut self, inputs: Vec<f64>, outputs: &mut Vec<f64>) -> Result<()> {
+    pub fn set_layer_activation(
+        &mut self,
+        layer_id: LayerID,
+        activation_fn: ActivationFn,
+    ) -> Result<()> {
         ensure!(
-            self.nodes
-                .iter()
-                .filter(|node| node.layer_id == LayerID::InputLayer)
-                .count()
-                == inputs.len(),
-            "Number of inputs does not match number of input nodes"
+            self.layers.contains(&layer_id),
+            "Layer with id {:?} does not exist",
+            layer_id
         );
 
-        for (node, input) in self
+        for node in self
             .nodes
             .iter_mut()
-            .filter(|node| node.layer_id == LayerID::InputLayer)
-            .zip(inputs)
+            .filter(|node| node.layer_id == layer_id)
         {
-            node.value = input;
-        }
-
-        for layer_id in self.layers.clone().iter() {
-            self.fire_layer(*layer_id)?;
+            node.activation_fn = activation_fn;
         }
 
-        let output_layer = self
-            .get_layer(LayerID::OutputLayer)
-            .context("Output layer does not exist")?
-            .iter()
-            .map(|node| node.value)
-            .collect::<Vec<f64>>();
-
-        outputs.clear();
-        outputs.extend(output_layer);
-
-        // clear all node's `value` fields
-        for node in self.nodes.iter_mut() {
-            node.reset();
+        match self
+            .layer_activation_fns
+            .iter_mut()
+            .find(|(id, _)| *id == layer_id)
+        {
+            Some((_, default)) => *default = activation_fn,
+            None => self.layer_activation_fns.push((layer_id, activation_fn)),
         }
 
         Ok(())
     }
 
-    pub(crate) fn fire_layer(&mut self, id: LayerID) -> Result<()> {
-        let ids = self
-            .clone()
-            .get_layer_mut(id)
-            .context("Layer does not exist")?
-            .iter()
-            .map(|node| node.id)
-            .collect::<Vec<usize>>();
-
-        let mut edges = Vec::new();
+    /// Changes every hidden and output node whose activation function equals
+    /// `from` to use `to`, returning how many nodes changed. Layer defaults,
+    /// and the network default, that equal `from` change too, so new nodes
+    /// match. Parametric functions such as `Step` only match with the same
+    /// parameter, and custom functions only match if they share a closure.
+    /// Input nodes are left alone; use `set_layer_activation` for them.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// network.set_layer_activation(LayerID::OutputLayer, ActivationFn::Sigmoid).unwrap();
+    ///
+    /// let changed = network.replace_activation(ActivationFn::ReLU, ActivationFn::GELU);
+    /// assert_eq!(changed, 3);
+    /// ```
+    pub fn replace_activation(&mut self, from: ActivationFn, to: ActivationFn) -> usize {
+        let mut changed = 0;
 
-        for id in ids {
-            for edge in self
-                .clone()
-                .edges
-                .iter()
-                .filter(|edge| edge.node_from_id == id)
-            {
-                edges.push((
-                    edge.clone().node_from_id,
-                    edge.clone().node_to_id,
-                    edge.clone().weight,
-                ))
-            }
+        for node in self.nodes.iter_mut().filter(|node| {
+            matches!(node.node_type, NodeType::HiddenNode | NodeType::OutputNode)
+                && node.activation_fn == from
+        }) {
+            node.activation_fn = to;
+            changed += 1;
         }
 
-        for (node_from_id, node_to_id, edge_weight) in edges {
-            let node_from_value = self
-                .get_node(node_from_id)
-                .context("Node from does not exist")?
-                .value;
-
-            let node_to = self
-                .get_node_mut(node_to_id)
-                .context("Node to does not exist")?;
-
-            node_to.add_value(node_from_value * edge_weight);
+        for (layer_id, activation_fn) in self.layer_activation_fns.iter_mut() {
+            if *layer_id != LayerID::InputLayer && *activation_fn == from {
+                *activation_fn = to;
+            }
         }
 
-        // get the next layer's id
-        let mut next_layer = match id {
-            LayerID::InputLayer => LayerID::HiddenLayer(0),
-            LayerID::HiddenLayer(id) => LayerID::HiddenLayer(id + 1),
-            LayerID::OutputLayer => LayerID::OutputLayer,
-        };
-
-        if !self.layers.contains(&next_layer) {
-            next_layer = LayerID::OutputLayer;
+        if self.activation_fn == from {
+            self.activation_fn = to;
         }
 
-        let mut layer = self
-            .get_layer_mut(next_layer)
-            .context("Layer does not exist")?;
+        changed
+    }
 
-        let nodes = layer
+    /// Changes every hidden and output node to use `activation_fn`, along
+    /// with the defaults for new nodes in those layers. Input nodes are left
+    /// alone; use `set_layer_activation` for them.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// network.set_all_activations(ActivationFn::Tanh);
+    /// ```
+    pub fn set_all_activations(&mut self, activation_fn: ActivationFn) {
+        for node in self
+            .nodes
             .iter_mut()
-            .filter(|node| node.layer_id == next_layer)
-            .collect::<Vec<&mut &mut Node>>();
-
-        for node in nodes {
-            node.add_value(node.bias);
-            node.value = node.activation_fn.run(node.value);
+            .filter(|node| matches!(node.node_type, NodeType::HiddenNode | NodeType::OutputNode))
+        {
+            node.activation_fn = activation_fn;
         }
 
-        Ok(())
+        self.layer_activation_fns
+            .retain(|(layer_id, _)| *layer_id == LayerID::InputLayer);
+        self.activation_fn = activation_fn;
     }
 
-    /// Adds the next available layer.
+    /// Marks the parameters of a layer as frozen, so that training leaves them
+    /// unchanged. This covers the biases of the layer's nodes and the weights
+    /// of the edges leading into them. Frozen parameters are still used when
+    /// firing. Nodes and edges added to the layer afterwards are not frozen.
     ///
     /// ### Example
     /// ```
     /// # use nnrs::{network::Network, layer::LayerID, activationfn::ActivationFn};
-    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
-    /// let layer_id: LayerID = network.add_layer();
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    ///
+    /// network.freeze_layer(LayerID::HiddenLayer(0)).unwrap();
+    /// assert!(network.is_layer_frozen(LayerID::HiddenLayer(0)).unwrap());
+    /// assert!(!network.is_layer_frozen(LayerID::OutputLayer).unwrap());
     /// ```
-    pub fn add_layer(&mut self) -> LayerID {
-        let mut hidden_layers = self
-            .layers
+    pub fn freeze_layer(&mut self, layer_id: LayerID) -> Result<()> {
+        self.set_layer_frozen(layer_id, true)
+    }
+
+    /// Unfreezes the parameters of a layer. See `freeze_layer`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    ///
+    /// network.freeze_layer(LayerID::HiddenLayer(0)).unwrap();
+    /// network.unfreeze_layer(LayerID::HiddenLayer(0)).unwrap();
+    /// assert!(!network.is_layer_frozen(LayerID::HiddenLayer(0)).unwrap());
+    /// ```
+    pub fn unfreeze_layer(&mut self, layer_id: LayerID) -> Result<()> {
+        self.set_layer_frozen(layer_id, false)
+    }
+
+    /// Returns true if every node in a layer, and every edge leading into
+    /// it, is frozen. See `freeze_layer`.
+    pub fn is_layer_frozen(&self, layer_id: LayerID) -> Result<bool> {
+        let node_ids = self.layer_node_ids(layer_id)?;
+
+        Ok(self
+            .nodes
             .iter()
-            .filter(|layer| matches!(layer, LayerID::HiddenLayer(_)))
-            .collect::<Vec<&LayerID>>();
+            .filter(|node| node.layer_id == layer_id)
+            .all(|node| node.frozen)
+            && self
+                .edges
+                .iter()
+                .filter(|edge| node_ids.contains(&edge.node_to_id))
+                .all(|edge| edge.frozen))
+    }
 
-        hidden_layers.sort();
+    fn set_layer_frozen(&mut self, layer_id: LayerID, frozen: bool) -> Result<()> {
+        let node_ids = self.layer_node_ids(layer_id)?;
 
-        let next_layer = match hidden_layers.last() {
-            Some(layer) => match layer {
-                LayerID::HiddenLayer(id) => LayerID::HiddenLayer(id + 1),
-                _ => unreachable!(),
-            },
-            None => LayerID::HiddenLayer(0),
-        };
+        for node in self
+            .nodes
+            .iter_mut()
+            .filter(|node| node.layer_id == layer_id)
+        {
+            node.frozen = frozen;
+        }
 
-        self.layers.push(next_layer);
+        for edge in self
+            .edges
+            .iter_mut()
+            .filter(|edge| node_ids.contains(&edge.node_to_id))
+        {
+            edge.frozen = frozen;
+        }
 
-        next_layer
+        Ok(())
     }
 
-    /// Serialize the network to a string
+    /// Runs the inputs of the network. Inputs are assigned to the input nodes
+    /// in the order given by `input_node_ids`, and outputs are returned in the
+    /// order given by `output_node_ids`. Each input node passes its input
+    /// through its activation function, which is `Linear` unless changed, for
+    /// example with `set_layer_activation(LayerID::InputLayer, ..)`. The bias
+    /// of an input node is not used.
+    ///
+    /// If the network is malformed, for example after deserializing a
+    /// hand-edited file, the error names the offending node or edge.
+    ///
+    /// An output node that no input can reach, such as every output of a
+    /// network fresh from `create`, still has a value: its activation function
+    /// applied to its bias. Its output does not depend on the inputs. Use
+    /// `is_fully_connected` to check for this, or `set_strict` to make `fire`
+    /// return an error instead.
     ///
     /// ### Example
     /// ```
@@ -224,150 +581,3321 @@ impl Network {
     /// # let output_node_id = network.output_node_ids().pop().unwrap();
     /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
     /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
-    /// let string = network.serialize().unwrap();
+    /// let mut output = vec![];
+    /// network.fire(vec![0.8], &mut output).unwrap();
     /// ```
-    pub fn serialize(&self) -> Result<String> {
-        serde_json::to_string(&self).context("Could not serialize network")
+    pub fn fire(&mut self, inputs: Vec<Float>, outputs: &mut Vec<Float>) -> Result<()> {
+        outputs.clear();
+        outputs.resize(self.output_size(), 0.0);
+
+        self.fire_into(&inputs, outputs)
     }
 
-    /// Deserialize a network from a string
+    /// Runs the inputs of the network, writing the outputs into a slice
+    /// instead of a `Vec`. The slice must have exactly one element per output
+    /// node.
     ///
     /// ### Example
     /// ```
     /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
     /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
-    /// # let layer_id = network.add_layer();
+    /// # let layerid = network.add_layer();
     /// # let input_node_id = network.input_node_ids().pop().unwrap();
-    /// # let hidden_node_id = Node::create(&mut network, LayerID::HiddenLayer(0), 0.0).unwrap();
+    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
     /// # let output_node_id = network.output_node_ids().pop().unwrap();
     /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
     /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
-    /// let string = network.serialize().unwrap();
-    /// let mut network2 = Network::deserialized(&string).unwrap();
+    /// let mut output = [0.0; 1];
+    /// network.fire_into(&[0.8], &mut output).unwrap();
+    /// ```
+    pub fn fire_into(&mut self, inputs: &[Float], outputs: &mut [Float]) -> Result<()> {
+        self.reset_after(|network| {
+            network.load_inputs(inputs)?;
+            network.propagate()?;
+            network.read_outputs(outputs)
+        })
+    }
+
+    /// Runs the inputs of the network like `fire`, but returns an error if any
+    /// node's value is NaN or infinite. The error names the first such node
+    /// in the order the network fires, which is where the problem started.
     ///
-    /// let mut outs = Vec::new();
-    /// let mut outs2 = Vec::new();
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn, Float};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, Float::MAX).unwrap();
     ///
-    /// network.fire(vec![0.8], &mut outs).unwrap();
-    /// network2.fire(vec![0.8], &mut outs2).unwrap();
+    /// let mut output = vec![];
+    /// network.fire_checked(vec![1.0], &mut output).unwrap();
     ///
-    /// assert_eq!(outs, outs2);
+    /// // doubling the largest float overflows to infinity
+    /// assert!(network.fire_checked(vec![2.0], &mut output).is_err());
     /// ```
-    pub fn deserialized(string: &str) -> Result<Self> {
-        serde_json::from_str(string).context("Could not deserialize network")
+    pub fn fire_checked(&mut self, inputs: Vec<Float>, outputs: &mut Vec<Float>) -> Result<()> {
+        outputs.clear();
+        outputs.resize(self.output_size(), 0.0);
+
+        self.reset_after(|network| {
+            network.load_inputs(&inputs)?;
+            network.propagate()?;
+            network.check_finite()?;
+            network.read_outputs(outputs)
+        })
     }
 
-    /// Serialize the network to a file
+    /// Errors on the first node, in firing order, whose value is not finite.
+    fn check_finite(&self) -> Result<()> {
+        for node_id in self.topological_node_ids() {
+            let node = self.get_node(node_id).context("Node does not exist")?;
+
+            ensure!(
+                node.value.is_finite(),
+                "Node {} in layer {:?} has a value of {}",
+                node.id,
+                node.layer_id,
+                node.value
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Runs the network with inputs and outputs keyed by node id instead of
+    /// position. Input nodes missing from `inputs` take the value `default`,
+    /// or cause an error if `default` is `None`. Every key of `inputs` must be
+    /// an input node. `outputs` is cleared and filled with one value per
+    /// output node.
     ///
     /// ### Example
     /// ```
-    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
-    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
-    /// # let layerid = network.add_layer();
-    /// # let input_node_id = network.input_node_ids().pop().unwrap();
-    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
-    /// # let output_node_id = network.output_node_ids().pop().unwrap();
-    /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
-    /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
-    /// network.save("network.json").unwrap();
+    /// # use std::collections::HashMap;
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_ids = network.input_node_ids();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_ids[0], output_node_id, 1.0).unwrap();
+    /// Edge::create(&mut network, input_node_ids[1], output_node_id, 2.0).unwrap();
+    ///
+    /// let inputs = HashMap::from([(input_node_ids[1], 1.5)]);
+    /// let mut outputs = HashMap::new();
+    /// network.fire_by_id(&inputs, Some(0.0), &mut outputs).unwrap();
+    /// assert_eq!(outputs[&output_node_id], 3.0);
+    ///
+    /// // without a default, every input must be given
+    /// assert!(network.fire_by_id(&inputs, None, &mut outputs).is_err());
     /// ```
-    pub fn save(&self, path: &str) -> Result<()> {
-        let mut file = File::create(path).context("Could not create file")?;
-        file.write_all(self.serialize()?.as_bytes())
-            .context("Could not write to file")?;
+    pub fn fire_by_id(
+        &mut self,
+        inputs: &HashMap<usize, Float>,
+        default: Option<Float>,
+        outputs: &mut HashMap<usize, Float>,
+    ) -> Result<()> {
+        let input_node_ids = self.input_node_ids();
+
+        for node_id in inputs.keys() {
+            ensure!(
+                input_node_ids.contains(node_id),
+                "Node with id {} is not an input node",
+                node_id
+            );
+        }
+
+        let values = input_node_ids
+            .iter()
+            .map(|node_id| {
+                inputs
+                    .get(node_id)
+                    .copied()
+                    .or(default)
+                    .with_context(|| format!("No input given for node {}", node_id))
+            })
+            .collect::<Result<Vec<Float>>>()?;
+
+        let mut output_values = vec![];
+        self.fire(values, &mut output_values)?;
+
+        outputs.clear();
+        outputs.extend(self.output_node_ids().into_iter().zip(output_values));
+
         Ok(())
     }
 
-    /// Deserialize a network from a file
+    /// Sets the stored value of a single input node, for use with
+    /// `fire_current`.
     ///
     /// ### Example
     /// ```
-    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
-    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
-    /// # let layerid = network.add_layer();
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_ids = network.input_node_ids();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_ids[0], output_node_id, 1.0).unwrap();
+    /// Edge::create(&mut network, input_node_ids[1], output_node_id, 2.0).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.set_input(input_node_ids[0], 1.0).unwrap();
+    /// network.set_input(input_node_ids[1], 1.0).unwrap();
+    /// network.fire_current(&mut output).unwrap();
+    /// assert_eq!(output, vec![3.0]);
+    ///
+    /// // only the first input changes, the second keeps its value
+    /// network.set_input(input_node_ids[0], 0.0).unwrap();
+    /// network.fire_current(&mut output).unwrap();
+    /// assert_eq!(output, vec![2.0]);
+    /// ```
+    pub fn set_input(&mut self, node_id: usize, value: Float) -> Result<()> {
+        let node = self
+            .get_node_mut(node_id)
+            .with_context(|| format!("Node with id {} does not exist", node_id))?;
+
+        ensure!(
+            node.node_type == NodeType::InputNode,
+            "Node with id {} is not an input node",
+            node_id
+        );
+
+        node.value = node.activate(value);
+
+        Ok(())
+    }
+
+    /// Runs the network using the values currently stored in the input nodes
+    /// (see `set_input`). Unlike `fire`, the input values are kept after
+    /// firing, so only the inputs that change need to be set before the next
+    /// call. Calling `fire` clears them.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// let mut output = vec![];
+    ///
+    /// // inputs that were never set are 0
+    /// network.fire_current(&mut output).unwrap();
+    /// ```
+    pub fn fire_current(&mut self, outputs: &mut Vec<Float>) -> Result<()> {
+        outputs.clear();
+        outputs.resize(self.output_size(), 0.0);
+
+        let result = self.propagate().and_then(|_| self.read_outputs(outputs));
+
+        for node in self
+            .nodes
+            .iter_mut()
+            .filter(|node| node.layer_id != LayerID::InputLayer)
+        {
+            node.reset();
+        }
+
+        result
+    }
+
+    /// Runs the inputs of the network and returns the value of every node
+    /// after activation, keyed by node id. Input nodes hold their input value
+    /// after their activation function.
+    /// Like `fire`, this leaves the network ready to be fired again.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// # let layerid = network.add_layer();
     /// # let input_node_id = network.input_node_ids().pop().unwrap();
-    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
     /// # let output_node_id = network.output_node_ids().pop().unwrap();
-    /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
-    /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
-    /// network.save("network.json").unwrap();
-    /// let mut network2 = Network::load("network.json").unwrap();
+    /// let hidden_node_id = Node::create(&mut network, layerid, 0.2).unwrap();
+    /// Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
     ///
-    /// let mut outs = Vec::new();
-    /// let mut outs2 = Vec::new();
+    /// let values = network.fire_and_capture(vec![0.8]).unwrap();
+    /// assert_eq!(values[&hidden_node_id], (0.8 * 0.5) + 0.2);
+    /// ```
+    pub fn fire_and_capture(&mut self, inputs: Vec<Float>) -> Result<HashMap<usize, Float>> {
+        self.reset_after(|network| {
+            network.load_inputs(&inputs)?;
+            network.propagate()?;
+
+            Ok(network
+                .nodes
+                .iter()
+                .map(|node| (node.id, node.value))
+                .collect::<HashMap<usize, Float>>())
+        })
+    }
+
+    /// Fires every input and collects statistics about each node's value,
+    /// keyed by node id. Useful for spotting dead ReLU nodes, which are never
+    /// active.
     ///
-    /// network.fire(vec![0.8], &mut outs).unwrap();
-    /// network2.fire(vec![0.8], &mut outs2).unwrap();
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::ReLU).unwrap();
+    /// let layer_id = network.add_layer();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let hidden_node_id = Node::create(&mut network, layer_id, 0.0).unwrap();
+    /// Edge::create(&mut network, input_node_id, hidden_node_id, -1.0).unwrap();
     ///
-    /// assert_eq!(outs, outs2);
+    /// let stats = network.activation_stats(&[vec![1.0], vec![2.0], vec![3.0]]).unwrap();
+    /// assert_eq!(stats[&input_node_id].mean, 2.0);
+    /// assert_eq!(stats[&hidden_node_id].fraction_active, 0.0);
     /// ```
-    pub fn load(path: &str) -> Result<Self> {
-        let mut file = File::open(path).context("Could not open file")?;
-        let mut string = String::new();
-        file.read_to_string(&mut string)
-            .context("Could not read file")?;
-        Self::deserialized(&string)
+    pub fn activation_stats(
+        &mut self,
+        inputs: &[Vec<Float>],
+    ) -> Result<HashMap<usize, NodeActivationStats>> {
+        ensure!(
+            !inputs.is_empty(),
+            "Cannot collect statistics without any samples"
+        );
+
+        // running mean, sum of squared differences from the mean (Welford's
+        // algorithm) and number of nonzero values, by node index
+        let mut means = vec![0.0; self.nodes.len()];
+        let mut squares = vec![0.0; self.nodes.len()];
+        let mut active = vec![0usize; self.nodes.len()];
+
+        self.reset_after(|network| {
+            for (sample, input) in inputs.iter().enumerate() {
+                network.load_inputs(input)?;
+                network.propagate()?;
+
+                for (index, node) in network.nodes.iter_mut().enumerate() {
+                    let delta = node.value - means[index];
+                    means[index] += delta / (sample + 1) as Float;
+                    squares[index] += delta * (node.value - means[index]);
+
+                    if node.value != 0.0 {
+                        active[index] += 1;
+                    }
+
+                    node.reset();
+                }
+            }
+
+            Ok(())
+        })?;
+
+        let count = inputs.len() as Float;
+
+        Ok(self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let stats = NodeActivationStats {
+                    mean: means[index],
+                    variance: squares[index] / count,
+                    fraction_active: active[index] as Float / count,
+                };
+
+                (node.id, stats)
+            })
+            .collect())
     }
 
-    /// Create a new network with the given number of inputs and outputs
+    /// Computes the Jacobian of the network at `inputs`: `jacobian[o][i]` is
+    /// the partial derivative of output `o` with respect to input `i`, in the
+    /// orders given by `output_node_ids` and `input_node_ids`. Derivatives
+    /// are carried forward alongside the values while firing. Step functions
+    /// have a derivative of 0, so an output behind one has a row of zeros.
+    /// For a network made with `create_classifier`, these are the derivatives
+    /// of the probabilities after softmax. Any output transform is included.
     ///
     /// ### Example
     /// ```
-    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
     /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_ids = network.input_node_ids();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_ids[0], output_node_id, 2.0).unwrap();
+    /// Edge::create(&mut network, input_node_ids[1], output_node_id, -3.0).unwrap();
+    ///
+    /// let jacobian = network.jacobian(&[1.0, 1.0]).unwrap();
+    /// assert_eq!(jacobian, vec![vec![2.0, -3.0]]);
     /// ```
-    pub fn create(input_ct: usize, output_ct: usize, activation_fn: ActivationFn) -> Result<Self> {
-        let mut network = Self {
-            edges: vec![],
-            nodes: vec![],
-            layers: vec![LayerID::InputLayer, LayerID::OutputLayer],
-            fitness: None,
-            activation_fn,
+    pub fn jacobian(&mut self, inputs: &[Float]) -> Result<Vec<Vec<Float>>> {
+        self.reset_after(|network| network.propagate_tangents(inputs))
+    }
+
+    /// Fires the network at `inputs` for `jacobian`, carrying forward the
+    /// derivative of every node's value with respect to every input. Leaves
+    /// the node values in place.
+    fn propagate_tangents(&mut self, inputs: &[Float]) -> Result<Vec<Vec<Float>>> {
+        self.check_strict()?;
+        self.load_inputs(inputs)?;
+        let cache = self.cache.as_ref().context("Fire cache does not exist")?;
+        let clamp = self.preactivation_clamp;
+
+        // the derivative of every node's value with respect to every input
+        let mut tangents = vec![vec![0.0; inputs.len()]; self.nodes.len()];
+
+        for (input, index) in cache.input_indexes.iter().enumerate() {
+            tangents[*index][input] = self.nodes[*index].activation_derivative(inputs[input]);
+        }
+
+        let add = |nodes: &mut [Node],
+                   tangents: &mut [Vec<Float>],
+                   from: usize,
+                   to: usize,
+                   weight: Float| {
+            let node_from_value = nodes[from].value;
+            nodes[to].add_value(node_from_value * weight);
+
+            let node_from_tangent = tangents[from].clone();
+
+            for (tangent, from_tangent) in tangents[to].iter_mut().zip(node_from_tangent) {
+                *tangent += from_tangent * weight;
+            }
         };
 
-        let mut input_ids = Vec::new();
-        let mut output_ids = Vec::new();
+        for layer in cache.layers.iter() {
+            for (node_from_index, node_to_index, edge_index) in layer.edges.iter() {
+                let edge = &self.edges[*edge_index];
 
-        for _ in 0..input_ct {
-            input_ids.push(Node::create(&mut network, LayerID::InputLayer, 0.0)?);
+                if !edge.enabled {
+                    continue;
+                }
+
+                let edge_weight = edge.weight;
+                add(
+                    &mut self.nodes,
+                    &mut tangents,
+                    *node_from_index,
+                    *node_to_index,
+                    edge_weight,
+                );
+            }
+
+            for (index, lateral) in layer.activate.iter().zip(layer.lateral.iter()) {
+                let node = &mut self.nodes[*index];
+
+                node.add_value(node.bias);
+
+                // a clamped value does not change with the inputs
+                let derivative = match clamp {
+                    Some((min, max)) if node.value < min || node.value > max => {
+                        node.value = node.value.clamp(min, max);
+                        0.0
+                    }
+                    _ => node.activation_derivative(node.value),
+                };
+                node.value = node.activate(node.value);
+
+                for tangent in tangents[*index].iter_mut() {
+                    *tangent *= derivative;
+                }
+
+                for (node_to_index, edge_index) in lateral.iter() {
+                    let edge = &self.edges[*edge_index];
+
+                    if !edge.enabled {
+                        continue;
+                    }
+
+                    let edge_weight = edge.weight;
+                    add(
+                        &mut self.nodes,
+                        &mut tangents,
+                        *index,
+                        *node_to_index,
+                        edge_weight,
+                    );
+                }
+            }
         }
 
-        for _ in 0..output_ct {
-            output_ids.push(Node::create(&mut network, LayerID::OutputLayer, 0.0)?);
+        let mut jacobian = cache
+            .output_indexes
+            .iter()
+            .map(|index| tangents[*index].clone())
+            .collect::<Vec<Vec<Float>>>();
+
+        if self.softmax_output {
+            // d softmax_o = p_o * (d logit_o - sum_k p_k * d logit_k)
+            let logits = cache
+                .output_indexes
+                .iter()
+                .map(|index| self.nodes[*index].value)
+                .collect::<Vec<Float>>();
+            let probabilities = softmax(&logits, 1.0);
+
+            let mean = (0..inputs.len())
+                .map(|input| {
+                    probabilities
+                        .iter()
+                        .zip(jacobian.iter())
+                        .map(|(probability, row)| probability * row[input])
+                        .sum::<Float>()
+                })
+                .collect::<Vec<Float>>();
+
+            for (probability, row) in probabilities.iter().zip(jacobian.iter_mut()) {
+                for (derivative, mean) in row.iter_mut().zip(mean.iter()) {
+                    *derivative = probability * (*derivative - mean);
+                }
+            }
         }
 
-        Ok(network)
+        if let Some((scale, _)) = &self.output_transform {
+            for (row, scale) in jacobian.iter_mut().zip(scale) {
+                for derivative in row.iter_mut() {
+                    *derivative *= scale;
+                }
+            }
+        }
+
+        Ok(jacobian)
     }
 
-    /// Get the ids of all the input nodes
+    /// Evaluates the network as a classifier. Each input is fired and the
+    /// index of the largest output is taken as the predicted class, which is
+    /// compared against the label at the same position.
     ///
     /// ### Example
     /// ```
-    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
-    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 2, ActivationFn::Linear).unwrap();
+    /// let inputs = network.input_node_ids();
+    /// let outputs = network.output_node_ids();
+    /// Edge::create(&mut network, inputs[0], outputs[0], 1.0).unwrap();
+    /// Edge::create(&mut network, inputs[1], outputs[1], 1.0).unwrap();
+    ///
+    /// let report = network
+    ///     .evaluate_classification(&[vec![1.0, 0.0], vec![0.0, 1.0], vec![0.0, 1.0]], &[0, 1, 0])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(report.confusion_matrix, vec![vec![1, 1], vec![0, 1]]);
+    /// ```
+    pub fn evaluate_classification(
+        &mut self,
+        inputs: &[Vec<Float>],
+        labels: &[usize],
+    ) -> Result<ClassificationReport> {
+        ensure!(
+            inputs.len() == labels.len(),
+            "Number of inputs does not match number of labels"
+        );
+        ensure!(!inputs.is_empty(), "Cannot evaluate without any samples");
+
+        let classes = self.output_size();
+
+        if let Some(label) = labels.iter().find(|label| **label >= classes) {
+            bail!(
+                "Label {} is out of range for a network with {} outputs",
+                label,
+                classes
+            );
+        }
+
+        let mut confusion_matrix = vec![vec![0; classes]; classes];
+        let mut correct = 0;
+        let mut outputs = vec![0.0; classes];
+
+        for (input, label) in inputs.iter().zip(labels) {
+            self.fire_into(input, &mut outputs)?;
+
+            let predicted = argmax(&outputs);
+            confusion_matrix[*label][predicted] += 1;
+
+            if predicted == *label {
+                correct += 1;
+            }
+        }
+
+        Ok(ClassificationReport {
+            accuracy: correct as f64 / inputs.len() as f64,
+            confusion_matrix,
+        })
+    }
+
+    /// Runs the inputs of the network and samples an output index from
+    /// `softmax(outputs / temperature)`, treating the outputs as logits. The
+    /// outputs are read before the softmax of `create_classifier` and before
+    /// any output transform. As
+    /// the temperature approaches 0 this approaches the index of the largest
+    /// output; high temperatures approach a uniform choice.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 2, ActivationFn::Linear).unwrap();
     /// let input_node_id = network.input_node_ids().pop().unwrap();
-    pub fn input_node_ids(&self) -> Vec<usize> {
-        self.nodes
-            .iter()
-            .filter(|n| n.layer_id == LayerID::InputLayer)
-            .map(|n| n.id)
-            .collect()
+    /// let output_node_ids = network.output_node_ids();
+    /// Edge::create(&mut network, input_node_id, output_node_ids[0], 1.0).unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_ids[1], -1.0).unwrap();
+    ///
+    /// let mut rng = rand::thread_rng();
+    /// let index = network.fire_sampled(vec![1.0], 0.01, &mut rng).unwrap();
+    /// assert_eq!(index, 0);
+    ///
+    /// assert!(network.fire_sampled(vec![1.0], 0.0, &mut rng).is_err());
+    /// ```
+    pub fn fire_sampled(
+        &mut self,
+        inputs: Vec<Float>,
+        temperature: Float,
+        rng: &mut impl Rng,
+    ) -> Result<usize> {
+        ensure!(temperature > 0.0, "Temperature must be greater than 0");
+
+        let mut outputs = vec![0.0; self.output_size()];
+        ensure!(!outputs.is_empty(), "Cannot sample without any outputs");
+
+        self.reset_after(|network| {
+            network.load_inputs(&inputs)?;
+            network.propagate()?;
+            network.read_raw_outputs(&mut outputs)
+        })?;
+
+        let probabilities = softmax(&outputs, temperature);
+        let mut sample = rng.gen::<Float>();
+
+        for (index, probability) in probabilities.iter().enumerate() {
+            if sample < *probability {
+                return Ok(index);
+            }
+
+            sample -= probability;
+        }
+
+        // rounding can leave a little probability unaccounted for
+        Ok(probabilities.len() - 1)
     }
 
-    /// Get the ids of all the output nodes
+    /// Runs the inputs of the network `samples` times with dropout, returning
+    /// the outputs of every run. In each run every hidden node is dropped
+    /// (its value set to 0) with probability `dropout_rate`, and the values
+    /// of the kept hidden nodes are divided by `1 - dropout_rate` so their
+    /// expected value is unchanged. The spread of the outputs is an estimate
+    /// of the network's uncertainty (Monte Carlo dropout).
     ///
     /// ### Example
     /// ```
-    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 16, 1], ActivationFn::ReLU).unwrap();
+    /// let mut rng = rand::thread_rng();
+    ///
+    /// let samples = network.fire_mc_dropout(vec![0.5, 1.0], 100, 0.2, &mut rng).unwrap();
+    /// assert_eq!(samples.len(), 100);
+    ///
+    /// let mean = samples.iter().map(|output| output[0]).sum::<nnrs::Float>() / 100.0;
+    /// ```
+    pub fn fire_mc_dropout(
+        &mut self,
+        inputs: Vec<Float>,
+        samples: usize,
+        dropout_rate: Float,
+        rng: &mut impl Rng,
+    ) -> Result<Vec<Vec<Float>>> {
+        ensure!(
+            (0.0..1.0).contains(&dropout_rate),
+            "Dropout rate must be at least 0 and less than 1"
+        );
+
+        let keep_scale = 1.0 / (1.0 - dropout_rate);
+        let mut runs = Vec::with_capacity(samples);
+
+        for _ in 0..samples {
+            let mut outputs = vec![0.0; self.output_size()];
+
+            self.reset_after(|network| {
+                network.load_inputs(&inputs)?;
+                network.propagate_masked(|node| {
+                    if node.node_type != NodeType::HiddenNode {
+                        1.0
+                    } else if rng.gen::<Float>() < dropout_rate {
+                        0.0
+                    } else {
+                        keep_scale
+                    }
+                })?;
+                network.read_outputs(&mut outputs)
+            })?;
+
+            runs.push(outputs);
+        }
+
+        Ok(runs)
+    }
+
+    /// Runs a sequence of inputs through the network, one time step at a
+    /// time, and returns the outputs of every step. Recurrent edges (see
+    /// `Edge::create_recurrent`) feed the value their source node had at the
+    /// end of the previous step into their target node. On the first step
+    /// every node's previous value is 0.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
     /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
     /// let output_node_id = network.output_node_ids().pop().unwrap();
+    ///
+    /// Edge::create(&mut network, input_node_id, output_node_id, 1.0).unwrap();
+    /// Edge::create_recurrent(&mut network, output_node_id, output_node_id, 1.0).unwrap();
+    ///
+    /// // the output accumulates a running sum of the inputs
+    /// let outputs = network.fire_sequence(&[vec![1.0], vec![2.0], vec![3.0]]).unwrap();
+    /// assert_eq!(outputs, vec![vec![1.0], vec![3.0], vec![6.0]]);
     /// ```
-    pub fn output_node_ids(&self) -> Vec<usize> {
-        self.nodes
+    pub fn fire_sequence(&mut self, inputs: &[Vec<Float>]) -> Result<Vec<Vec<Float>>> {
+        self.build_cache()?;
+        let cache = self.cache.as_ref().context("Fire cache does not exist")?;
+
+        let mut recurrent_edges = Vec::new();
+
+        for edge in self
+            .edges
             .iter()
-            .filter(|n| n.layer_id == LayerID::OutputLayer)
-            .map(|n| n.id)
-            .collect()
+            .filter(|edge| edge.recurrent && edge.enabled)
+        {
+            // the cache checks that every edge's nodes exist
+            let node_from_index = cache.indexes[&edge.node_from_id];
+            let node_to_index = cache.indexes[&edge.node_to_id];
+
+            recurrent_edges.push((node_from_index, node_to_index, edge.weight));
+        }
+
+        // the value of every node at the end of the previous step, by index
+        let mut previous_values = vec![0.0; self.nodes.len()];
+        let mut sequence = Vec::with_capacity(inputs.len());
+
+        self.reset_after(|network| {
+            for step in inputs {
+                network.load_inputs(step)?;
+
+                for (node_from_index, node_to_index, edge_weight) in recurrent_edges.iter() {
+                    network.nodes[*node_to_index]
+                        .add_value(previous_values[*node_from_index] * edge_weight);
+                }
+
+                network.propagate()?;
+
+                let mut outputs = vec![0.0; network.output_size()];
+                network.read_outputs(&mut outputs)?;
+                sequence.push(outputs);
+
+                for (previous_value, node) in
+                    previous_values.iter_mut().zip(network.nodes.iter_mut())
+                {
+                    *previous_value = node.value;
+                    node.reset();
+                }
+            }
+
+            Ok(sequence)
+        })
+    }
+
+    /// Runs `fire`, then clears every node's value whether or not it
+    /// succeeded, so a fire that fails partway leaves no values behind to be
+    /// added to by the next one.
+    fn reset_after<T>(&mut self, fire: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let result = fire(self);
+
+        for node in self.nodes.iter_mut() {
+            node.reset();
+        }
+
+        result
+    }
+
+    /// Drops the cached firing order. Must be called whenever nodes or edges
+    /// are added or removed, or a node's layer changes.
+    pub(crate) fn invalidate_cache(&mut self) {
+        self.cache = None;
+    }
+
+    pub(crate) fn build_cache(&mut self) -> Result<()> {
+        if self.cache.is_none() {
+            self.cache = Some(FireCache::new(self)?);
+        }
+
+        Ok(())
+    }
+
+    /// In strict mode, errors on the first output node that no input node
+    /// can reach. See `set_strict`.
+    fn check_strict(&mut self) -> Result<()> {
+        self.build_cache()?;
+        let cache = self.cache.as_ref().context("Fire cache does not exist")?;
+
+        if self.strict {
+            if let Some(node_id) = cache.disconnected_outputs.first() {
+                bail!(
+                    "Output node {} is not connected to any input node; the network is in strict mode",
+                    node_id
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn load_inputs(&mut self, inputs: &[Float]) -> Result<()> {
+        ensure!(
+            self.input_size() == inputs.len(),
+            "Number of inputs does not match number of input nodes"
+        );
+
+        self.build_cache()?;
+        let cache = self.cache.as_ref().context("Fire cache does not exist")?;
+
+        for (index, input) in cache.input_indexes.iter().zip(inputs) {
+            let node = &mut self.nodes[*index];
+            node.value = node.activate(*input);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn propagate(&mut self) -> Result<()> {
+        self.propagate_masked(|_| 1.0)
+    }
+
+    /// Propagates like `propagate`, multiplying each node's value by
+    /// `mask(node)` after it activates.
+    fn propagate_masked(&mut self, mut mask: impl FnMut(&Node) -> Float) -> Result<()> {
+        // every way of firing the network goes through here, before any
+        // node past the inputs has a value
+        self.check_strict()?;
+        let cache = self.cache.as_ref().context("Fire cache does not exist")?;
+        let clamp = self.preactivation_clamp;
+
+        for layer in cache.layers.iter() {
+            for (node_from_index, node_to_index, edge_index) in layer.edges.iter() {
+                let edge = &self.edges[*edge_index];
+
+                if !edge.enabled {
+                    continue;
+                }
+
+                let node_from_value = self.nodes[*node_from_index].value;
+                let edge_weight = edge.weight;
+
+                self.nodes[*node_to_index].add_value(node_from_value * edge_weight);
+            }
+
+            for (index, lateral) in layer.activate.iter().zip(layer.lateral.iter()) {
+                let node = &mut self.nodes[*index];
+
+                node.add_value(node.bias);
+
+                if let Some((min, max)) = clamp {
+                    node.value = node.value.clamp(min, max);
+                }
+
+                node.value = node.activate(node.value) * mask(node);
+
+                let node_from_value = node.value;
+
+                for (node_to_index, edge_index) in lateral.iter() {
+                    let edge = &self.edges[*edge_index];
+
+                    if edge.enabled {
+                        self.nodes[*node_to_index].add_value(node_from_value * edge.weight);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn read_outputs(&mut self, outputs: &mut [Float]) -> Result<()> {
+        self.read_raw_outputs(outputs)?;
+
+        if self.softmax_output {
+            let probabilities = softmax(outputs, 1.0);
+            outputs.copy_from_slice(&probabilities);
+        }
+
+        if let Some((scale, offset)) = &self.output_transform {
+            ensure!(
+                scale.len() == outputs.len(),
+                "Output transform has {} values, but there are {} outputs",
+                scale.len(),
+                outputs.len()
+            );
+
+            for ((output, scale), offset) in outputs.iter_mut().zip(scale).zip(offset) {
+                *output = *output * scale + offset;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies the output node values, without the softmax or output
+    /// transform.
+    fn read_raw_outputs(&mut self, outputs: &mut [Float]) -> Result<()> {
+        ensure!(
+            self.output_size() == outputs.len(),
+            "Number of outputs does not match number of output nodes"
+        );
+
+        ensure!(
+            self.layers.contains(&LayerID::OutputLayer),
+            "Output layer does not exist"
+        );
+
+        self.build_cache()?;
+        let cache = self.cache.as_ref().context("Fire cache does not exist")?;
+
+        for (output, index) in outputs.iter_mut().zip(cache.output_indexes.iter()) {
+            *output = self.nodes[*index].value;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the next available layer.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, layer::LayerID, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// let layer_id: LayerID = network.add_layer();
+    /// ```
+    pub fn add_layer(&mut self) -> LayerID {
+        let mut hidden_layers = self
+            .layers
+            .iter()
+            .filter(|layer| matches!(layer, LayerID::HiddenLayer(_)))
+            .collect::<Vec<&LayerID>>();
+
+        hidden_layers.sort();
+
+        let next_layer = match hidden_layers.last() {
+            Some(layer) => match layer {
+                LayerID::HiddenLayer(id) => LayerID::HiddenLayer(id + 1),
+                _ => unreachable!(),
+            },
+            None => LayerID::HiddenLayer(0),
+        };
+
+        self.layers.push(next_layer);
+        self.layers.sort();
+        self.invalidate_cache();
+
+        next_layer
+    }
+
+    /// Inserts a new, empty hidden layer directly after `after`, shifting the
+    /// ids of every later hidden layer (and of the nodes in them) up by one.
+    /// The order of the existing layers does not change, so every edge still
+    /// points to a later layer. Returns the id of the new layer.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 3, 1], ActivationFn::ReLU).unwrap();
+    ///
+    /// let layer_id = network.insert_layer(LayerID::HiddenLayer(0)).unwrap();
+    /// assert_eq!(layer_id, LayerID::HiddenLayer(1));
+    ///
+    /// // the old second hidden layer moved up
+    /// assert_eq!(network.layer_node_ids(LayerID::HiddenLayer(2)).unwrap().len(), 3);
+    /// ```
+    pub fn insert_layer(&mut self, after: LayerID) -> Result<LayerID> {
+        let index = match after {
+            LayerID::InputLayer => 0,
+            LayerID::HiddenLayer(id) => id + 1,
+            LayerID::OutputLayer => bail!("Cannot insert a layer after the output layer"),
+        };
+
+        ensure!(
+            self.layers.contains(&after),
+            "Layer with id {:?} does not exist",
+            after
+        );
+
+        let shift = |layer_id: &mut LayerID| {
+            if let LayerID::HiddenLayer(id) = layer_id {
+                if *id >= index {
+                    *id += 1;
+                }
+            }
+        };
+
+        self.layers.iter_mut().for_each(shift);
+        self.nodes
+            .iter_mut()
+            .for_each(|node| shift(&mut node.layer_id));
+        self.layer_activation_fns
+            .iter_mut()
+            .for_each(|(layer_id, _)| shift(layer_id));
+
+        let layer_id = LayerID::HiddenLayer(index);
+
+        self.layers.push(layer_id);
+        self.layers.sort();
+        self.invalidate_cache();
+
+        Ok(layer_id)
+    }
+
+    /// Removes every hidden layer without any nodes, and renumbers the
+    /// remaining hidden layers to `HiddenLayer(0)`, `HiddenLayer(1)`, and so
+    /// on, keeping their order. Edges connect nodes, not layers, so they are
+    /// unaffected. Returns the number of layers removed.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// let empty_layer_id = network.insert_layer(LayerID::InputLayer).unwrap();
+    /// assert_eq!(empty_layer_id, LayerID::HiddenLayer(0));
+    ///
+    /// assert_eq!(network.trim_empty_layers(), 1);
+    /// assert_eq!(network.layer_node_ids(LayerID::HiddenLayer(0)).unwrap().len(), 3);
+    /// ```
+    pub fn trim_empty_layers(&mut self) -> usize {
+        let mut hidden_layers = self
+            .layers
+            .iter()
+            .filter(|layer_id| layer_id.is_hidden())
+            .copied()
+            .collect::<Vec<LayerID>>();
+        hidden_layers.sort();
+
+        let kept = hidden_layers
+            .iter()
+            .filter(|layer_id| self.nodes.iter().any(|node| node.layer_id == **layer_id))
+            .copied()
+            .collect::<Vec<LayerID>>();
+        let removed = hidden_layers.len() - kept.len();
+
+        let renumber = |layer_id: LayerID| match kept.iter().position(|id| *id == layer_id) {
+            Some(index) => LayerID::HiddenLayer(index),
+            None => layer_id,
+        };
+
+        for node in self.nodes.iter_mut() {
+            node.layer_id = renumber(node.layer_id);
+        }
+
+        self.layer_activation_fns
+            .retain(|(layer_id, _)| !layer_id.is_hidden() || kept.contains(layer_id));
+        for (layer_id, _) in self.layer_activation_fns.iter_mut() {
+            *layer_id = renumber(*layer_id);
+        }
+
+        self.layers = self
+            .layers
+            .iter()
+            .filter(|layer_id| !layer_id.is_hidden() || kept.contains(layer_id))
+            .map(|layer_id| renumber(*layer_id))
+            .collect();
+        self.layers.sort();
+        self.invalidate_cache();
+
+        removed
+    }
+
+    /// Enables or disables an edge. Disabled edges are kept, along with their
+    /// weight, but are skipped when firing, as if they were not there. Edges
+    /// are enabled when created.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// let edge_id = Edge::create(&mut network, input_node_id, output_node_id, 2.0).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.set_edge_enabled(edge_id, false).unwrap();
+    /// network.fire(vec![1.0], &mut output).unwrap();
+    /// assert_eq!(output, vec![0.0]);
+    ///
+    /// network.set_edge_enabled(edge_id, true).unwrap();
+    /// network.fire(vec![1.0], &mut output).unwrap();
+    /// assert_eq!(output, vec![2.0]);
+    /// ```
+    pub fn set_edge_enabled(&mut self, edge_id: usize, enabled: bool) -> Result<()> {
+        let edge = self
+            .edges
+            .iter_mut()
+            .find(|edge| edge.id == edge_id)
+            .with_context(|| format!("Edge with id {} does not exist", edge_id))?;
+
+        edge.enabled = enabled;
+
+        // whether outputs are connected depends on which edges are enabled
+        self.invalidate_cache();
+
+        Ok(())
+    }
+
+    /// Whether an edge is enabled, or `None` if it does not exist. See
+    /// `set_edge_enabled`.
+    pub fn is_edge_enabled(&self, edge_id: usize) -> Option<bool> {
+        self.get_edge(edge_id).map(|edge| edge.enabled)
+    }
+
+    /// Splits an edge in two by inserting a new node in between, as in NEAT's
+    /// "add node" mutation. The edge is disabled, not removed, so its history
+    /// is kept, and is bypassed by an edge into the new node with a weight of
+    /// 1 and an edge out of it with the old weight. Both new edges are frozen
+    /// if the old one was. The new node is placed in the first layer after
+    /// the edge's source, and a new hidden layer is inserted if the edge
+    /// connects adjacent layers. Lateral, recurrent and disabled edges cannot
+    /// be split. If an error is returned, the network is unchanged.
+    ///
+    /// Returns the id of the new node.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// let edge_id = Edge::create(&mut network, input_node_id, output_node_id, 1.5).unwrap();
+    ///
+    /// let node_id = network.split_edge(edge_id).unwrap();
+    /// assert_eq!(network.is_edge_enabled(edge_id), Some(false));
+    /// ```
+    pub fn split_edge(&mut self, edge_id: usize) -> Result<usize> {
+        let edge = self
+            .get_edge(edge_id)
+            .with_context(|| format!("Edge with id {} does not exist", edge_id))?
+            .clone();
+
+        ensure!(
+            !edge.lateral,
+            "Edge {} is lateral and cannot be split",
+            edge_id
+        );
+        ensure!(
+            !edge.recurrent,
+            "Edge {} is recurrent and cannot be split",
+            edge_id
+        );
+        ensure!(
+            edge.enabled,
+            "Edge {} is disabled and cannot be split",
+            edge_id
+        );
+
+        let layer_from = self
+            .get_node(edge.node_from_id)
+            .context("Node from does not exist")?
+            .layer_id;
+
+        let layer_to = self
+            .get_node(edge.node_to_id)
+            .context("Node to does not exist")?
+            .layer_id;
+
+        // everything below assumes the edge points forwards, so check before
+        // changing anything
+        ensure!(
+            layer_from < layer_to,
+            "Edge {} goes from layer {:?} to layer {:?}, which is not forwards",
+            edge_id,
+            layer_from,
+            layer_to
+        );
+
+        let next_layer = self
+            .layers
+            .iter()
+            .filter(|layer| **layer > layer_from)
+            .min()
+            .copied();
+
+        let layer_id = match next_layer {
+            Some(layer) if layer < layer_to => layer,
+            _ => self.insert_layer(layer_from)?,
+        };
+
+        let node_id = Node::create(self, layer_id, 0.0)?;
+
+        self.set_edge_enabled(edge_id, false)?;
+        self.invalidate_cache();
+
+        let new_edge_ids = [
+            Edge::create(self, edge.node_from_id, node_id, 1.0)?,
+            Edge::create(self, node_id, edge.node_to_id, edge.weight)?,
+        ];
+
+        for new_edge in self
+            .edges
+            .iter_mut()
+            .filter(|new_edge| new_edge_ids.contains(&new_edge.id))
+        {
+            new_edge.frozen = edge.frozen;
+        }
+
+        Ok(node_id)
+    }
+
+    /// Adds a bias node to a layer. A bias node always has a value of 1, so the
+    /// weights of the edges leaving it act as biases for the nodes they lead
+    /// to. This is an alternative to the `bias` every node already has, and
+    /// the two can be used together. Bias nodes cannot have incoming edges,
+    /// their own bias and activation function are ignored, and a bias node in
+    /// the input layer is not an input: it is not part of `input_node_ids`
+    /// and does not take a value in `fire`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let bias_node_id = network.add_bias_node(LayerID::InputLayer).unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, bias_node_id, output_node_id, 0.3).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.fire(vec![0.0], &mut output).unwrap();
+    /// assert_eq!(output, vec![0.3]);
+    /// ```
+    pub fn add_bias_node(&mut self, layer_id: LayerID) -> Result<usize> {
+        ensure!(
+            layer_id != LayerID::OutputLayer,
+            "Bias nodes cannot be added to the output layer"
+        );
+
+        let id = Node::create(self, layer_id, 0.0)?;
+        let node = self.get_node_mut(id).context("Bias node does not exist")?;
+
+        node.node_type = NodeType::BiasNode;
+        node.reset();
+
+        self.invalidate_cache();
+
+        Ok(id)
+    }
+
+    /// Removes a node, along with every edge connected to it. The ids of the
+    /// removed node and edges are not reused.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// # let layerid = network.add_layer();
+    /// # let input_node_id = network.input_node_ids().pop().unwrap();
+    /// # let output_node_id = network.output_node_ids().pop().unwrap();
+    /// let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
+    /// Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
+    ///
+    /// network.remove_node(hidden_node_id).unwrap();
+    /// ```
+    pub fn remove_node(&mut self, node_id: usize) -> Result<()> {
+        ensure!(
+            self.get_node(node_id).is_some(),
+            "Node with id {} does not exist",
+            node_id
+        );
+
+        self.nodes.retain(|node| node.id != node_id);
+        self.edges
+            .retain(|edge| edge.node_from_id != node_id && edge.node_to_id != node_id);
+        self.invalidate_cache();
+
+        Ok(())
+    }
+
+    /// Removes the edges with the smallest absolute weights until at most
+    /// `max_edges` remain, then removes hidden nodes left without outgoing
+    /// edges, whose values can no longer reach the outputs. Edges whose removal
+    /// would disconnect an output from every input are kept for as long as
+    /// other edges can be removed instead.
+    ///
+    /// Check `connected_outputs` in the returned report: if it is 0, the cap
+    /// was too low to keep any output connected to the inputs.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[4, 8, 2], ActivationFn::ReLU).unwrap();
+    /// let report = network.sparsify(20);
+    ///
+    /// assert!(network.parameter_count().weights <= 20);
+    /// assert!(report.edges_removed >= 28);
+    /// assert_eq!(report.connected_outputs, 2);
+    /// ```
+    pub fn sparsify(&mut self, max_edges: usize) -> SparsifyReport {
+        let edges_before = self.edges.len();
+        let nodes_before = self.nodes.len();
+
+        if self.edges.len() > max_edges {
+            let mut candidates = self
+                .edges
+                .iter()
+                .map(|edge| (edge.weight.abs(), edge.id))
+                .collect::<Vec<(Float, usize)>>();
+            candidates.sort_by(|(a, a_id), (b, b_id)| a.total_cmp(b).then(a_id.cmp(b_id)));
+
+            // reachability from the inputs along enabled, non-recurrent edges,
+            // updated as edges are removed instead of rescanning the network
+            let mut incoming: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+            let mut outgoing: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+            let mut endpoints = HashMap::new();
+
+            for edge in self.edges.iter() {
+                if edge.enabled && !edge.recurrent {
+                    incoming
+                        .entry(edge.node_to_id)
+                        .or_default()
+                        .push((edge.id, edge.node_from_id));
+                    outgoing
+                        .entry(edge.node_from_id)
+                        .or_default()
+                        .push((edge.id, edge.node_to_id));
+                    endpoints.insert(edge.id, (edge.node_from_id, edge.node_to_id));
+                }
+            }
+
+            let input_node_ids = self
+                .input_node_ids()
+                .into_iter()
+                .collect::<HashSet<usize>>();
+            let output_node_ids = self
+                .output_node_ids()
+                .into_iter()
+                .collect::<HashSet<usize>>();
+            let mut reached = self.reachable_from_inputs();
+            let mut removed = HashSet::new();
+            let mut kept = Vec::new();
+
+            for (_, edge_id) in candidates {
+                if self.edges.len() - removed.len() <= max_edges {
+                    break;
+                }
+
+                removed.insert(edge_id);
+
+                let (node_from_id, node_to_id) = match endpoints.get(&edge_id) {
+                    Some(endpoints) => *endpoints,
+                    None => continue,
+                };
+
+                if !reached.contains(&node_from_id) {
+                    continue;
+                }
+
+                // edges point forwards, so a node stays reachable as long as
+                // one of its remaining incoming edges comes from a reachable
+                // node; whenever a node is lost, the nodes it feeds are checked
+                let mut lost = Vec::new();
+                let mut stack = vec![node_to_id];
+
+                while let Some(node_id) = stack.pop() {
+                    let still_reached = input_node_ids.contains(&node_id)
+                        || incoming
+                            .get(&node_id)
+                            .into_iter()
+                            .flatten()
+                            .any(|(id, from)| !removed.contains(id) && reached.contains(from));
+
+                    if still_reached || !reached.remove(&node_id) {
+                        continue;
+                    }
+
+                    lost.push(node_id);
+
+                    for (id, to) in outgoing.get(&node_id).into_iter().flatten() {
+                        if !removed.contains(id) {
+                            stack.push(*to);
+                        }
+                    }
+                }
+
+                if lost.iter().any(|node_id| output_node_ids.contains(node_id)) {
+                    removed.remove(&edge_id);
+                    reached.extend(lost);
+                    kept.push(edge_id);
+                }
+            }
+
+            // there is no way around disconnecting an output
+            for edge_id in kept {
+                if self.edges.len() - removed.len() <= max_edges {
+                    break;
+                }
+
+                removed.insert(edge_id);
+            }
+
+            self.edges.retain(|edge| !removed.contains(&edge.id));
+            self.invalidate_cache();
+
+            loop {
+                let feeding = self
+                    .edges
+                    .iter()
+                    .map(|edge| edge.node_from_id)
+                    .collect::<HashSet<usize>>();
+                let dead = self
+                    .nodes
+                    .iter()
+                    .filter(|node| node.node_type == NodeType::HiddenNode)
+                    .filter(|node| !feeding.contains(&node.id))
+                    .map(|node| node.id)
+                    .collect::<HashSet<usize>>();
+
+                if dead.is_empty() {
+                    break;
+                }
+
+                self.nodes.retain(|node| !dead.contains(&node.id));
+                self.edges.retain(|edge| !dead.contains(&edge.node_to_id));
+            }
+        }
+
+        SparsifyReport {
+            edges_removed: edges_before - self.edges.len(),
+            nodes_removed: nodes_before - self.nodes.len(),
+            connected_outputs: self.connected_outputs(),
+        }
+    }
+
+    /// Reduces the network to a skeleton: for each output node, the path from
+    /// an input node with the largest total absolute weight is kept, and every
+    /// other edge is removed, along with hidden and bias nodes that are no
+    /// longer on a kept path. Only enabled, non-recurrent edges can be part of a path. An
+    /// output that no input can reach loses all of its edges.
+    ///
+    /// This is lossy: everything off the kept paths stops contributing, so the
+    /// outputs change. It is meant for extreme compression or for seeing which
+    /// connections dominate each output, not as a drop-in replacement.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[4, 8, 2], ActivationFn::ReLU).unwrap();
+    /// let report = network.skeletonize();
+    ///
+    /// // two outputs, each with a path of two edges; the paths can share an edge
+    /// assert!(network.parameter_count().weights <= 4);
+    /// assert!(report.edges_removed >= 44);
+    /// assert_eq!(report.connected_outputs, 2);
+    /// ```
+    pub fn skeletonize(&mut self) -> SparsifyReport {
+        let edges_before = self.edges.len();
+        let nodes_before = self.nodes.len();
+
+        let mut incoming: HashMap<usize, Vec<&Edge>> = HashMap::new();
+
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| !edge.recurrent && edge.enabled)
+        {
+            incoming.entry(edge.node_to_id).or_default().push(edge);
+        }
+
+        // the heaviest path from any input to each node, as its total weight
+        // and the last edge on it; every edge points forwards in this order
+        let mut best: HashMap<usize, (Float, Option<&Edge>)> = HashMap::new();
+        let input_node_ids = self.input_node_ids();
+
+        for node_id in self.topological_node_ids() {
+            if input_node_ids.contains(&node_id) {
+                best.insert(node_id, (0.0, None));
+                continue;
+            }
+
+            let mut heaviest: Option<(Float, &Edge)> = None;
+
+            for edge in incoming.get(&node_id).into_iter().flatten() {
+                if let Some((total, _)) = best.get(&edge.node_from_id) {
+                    let total = total + edge.weight.abs();
+
+                    match heaviest {
+                        Some((max, _)) if max >= total => {}
+                        _ => heaviest = Some((total, edge)),
+                    }
+                }
+            }
+
+            if let Some((total, edge)) = heaviest {
+                best.insert(node_id, (total, Some(edge)));
+            }
+        }
+
+        let mut kept_edges = HashSet::new();
+        let mut kept_nodes = HashSet::new();
+
+        for output_node_id in self.output_node_ids() {
+            let mut node_id = output_node_id;
+
+            while let Some((_, Some(edge))) = best.get(&node_id) {
+                kept_edges.insert(edge.id);
+                kept_nodes.insert(edge.node_from_id);
+                node_id = edge.node_from_id;
+            }
+        }
+
+        self.edges.retain(|edge| kept_edges.contains(&edge.id));
+        self.nodes.retain(|node| match node.node_type {
+            NodeType::InputNode | NodeType::OutputNode => true,
+            _ => kept_nodes.contains(&node.id),
+        });
+        self.invalidate_cache();
+
+        SparsifyReport {
+            edges_removed: edges_before - self.edges.len(),
+            nodes_removed: nodes_before - self.nodes.len(),
+            connected_outputs: self.connected_outputs(),
+        }
+    }
+
+    /// Checks that every edge points forwards: from a lower layer to a higher
+    /// one, or for lateral edges, to a higher id in the same layer. Recurrent
+    /// edges are exempt. The error names the first offending edge and the
+    /// layers of its nodes.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// network.verify_feedforward_invariant().unwrap();
+    /// ```
+    pub fn verify_feedforward_invariant(&self) -> Result<()> {
+        for edge in self.edges.iter().filter(|edge| !edge.recurrent) {
+            let layer_from = self
+                .get_node(edge.node_from_id)
+                .with_context(|| {
+                    format!(
+                        "Node {} of edge {} does not exist",
+                        edge.node_from_id, edge.id
+                    )
+                })?
+                .layer_id;
+
+            let layer_to = self
+                .get_node(edge.node_to_id)
+                .with_context(|| {
+                    format!(
+                        "Node {} of edge {} does not exist",
+                        edge.node_to_id, edge.id
+                    )
+                })?
+                .layer_id;
+
+            if edge.lateral {
+                ensure!(
+                    layer_from == layer_to && edge.node_from_id < edge.node_to_id,
+                    "Lateral edge {} goes from node {} in layer {:?} to node {} in layer {:?}",
+                    edge.id,
+                    edge.node_from_id,
+                    layer_from,
+                    edge.node_to_id,
+                    layer_to
+                );
+            } else {
+                ensure!(
+                    layer_to > layer_from,
+                    "Edge {} goes from layer {:?} to layer {:?}, which is not later",
+                    edge.id,
+                    layer_from,
+                    layer_to
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the network as a weighted adjacency matrix. Returns the node ids,
+    /// sorted by layer and then by id, and a matrix where `matrix[i][j]` is the
+    /// weight of the edge from node `ids[i]` to node `ids[j]`, or 0 if there is
+    /// no such edge or it is disabled.
+    ///
+    /// Non-recurrent edges always point to a later layer, or to a higher id in
+    /// the same layer, so they all lie above the diagonal. Only recurrent edges
+    /// can appear on or below it.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, 0.5).unwrap();
+    ///
+    /// let (ids, matrix) = network.adjacency_matrix();
+    /// assert_eq!(ids, vec![input_node_id, output_node_id]);
+    /// assert_eq!(matrix, vec![vec![0.0, 0.5], vec![0.0, 0.0]]);
+    /// ```
+    pub fn adjacency_matrix(&self) -> (Vec<usize>, Vec<Vec<Float>>) {
+        let ids = self.topological_node_ids();
+        let positions = ids
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (*id, position))
+            .collect::<HashMap<usize, usize>>();
+
+        let mut matrix = vec![vec![0.0; ids.len()]; ids.len()];
+
+        for edge in self.edges.iter().filter(|edge| edge.enabled) {
+            if let (Some(from), Some(to)) = (
+                positions.get(&edge.node_from_id),
+                positions.get(&edge.node_to_id),
+            ) {
+                matrix[*from][*to] = edge.weight;
+            }
+        }
+
+        (ids, matrix)
+    }
+
+    /// Get the ids of the edges that end at a node, sorted by id. Recurrent,
+    /// lateral and disabled edges are included. A node that does not exist
+    /// has no edges.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_ids = network.input_node_ids();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// let first = Edge::create(&mut network, input_node_ids[0], output_node_id, 1.0).unwrap();
+    /// let second = Edge::create(&mut network, input_node_ids[1], output_node_id, 1.0).unwrap();
+    ///
+    /// assert_eq!(network.incoming_edges(output_node_id), vec![first, second]);
+    /// assert_eq!(network.outgoing_edges(input_node_ids[1]), vec![second]);
+    /// assert_eq!(network.edge_endpoints(first), Some((input_node_ids[0], output_node_id)));
+    /// ```
+    pub fn incoming_edges(&self, node_id: usize) -> Vec<usize> {
+        self.edge_ids_where(|edge| edge.node_to_id == node_id)
+    }
+
+    /// Get the ids of the edges that start at a node, sorted by id. Recurrent,
+    /// lateral and disabled edges are included. A node that does not exist
+    /// has no edges.
+    pub fn outgoing_edges(&self, node_id: usize) -> Vec<usize> {
+        self.edge_ids_where(|edge| edge.node_from_id == node_id)
+    }
+
+    /// Get the ids of the nodes an edge goes from and to, or `None` if the
+    /// edge does not exist.
+    pub fn edge_endpoints(&self, edge_id: usize) -> Option<(usize, usize)> {
+        self.get_edge(edge_id)
+            .map(|edge| (edge.node_from_id, edge.node_to_id))
+    }
+
+    fn edge_ids_where(&self, predicate: impl Fn(&Edge) -> bool) -> Vec<usize> {
+        let mut ids = self
+            .edges
+            .iter()
+            .filter(|edge| predicate(edge))
+            .map(|edge| edge.id)
+            .collect::<Vec<usize>>();
+        ids.sort();
+        ids
+    }
+
+    /// Get the ids of every node reachable from a node by following enabled
+    /// edges forwards, including recurrent and lateral edges. The node itself
+    /// is only included if it is part of a cycle.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::ReLU).unwrap();
+    /// let layer_id = network.add_layer();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let hidden_node_id = Node::create(&mut network, layer_id, 0.0).unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, hidden_node_id, 1.0).unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 1.0).unwrap();
+    ///
+    /// let reachable = network.reachable_from(input_node_id).unwrap();
+    /// assert!(reachable.contains(&output_node_id));
+    /// ```
+    pub fn reachable_from(&self, node_id: usize) -> Result<HashSet<usize>> {
+        self.walk(node_id, |edge| (edge.node_from_id, edge.node_to_id))
+    }
+
+    /// Get the ids of every node that can reach a node by following enabled
+    /// edges forwards, including recurrent and lateral edges. The node itself
+    /// is only included if it is part of a cycle.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::ReLU).unwrap();
+    /// let layer_id = network.add_layer();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let hidden_node_id = Node::create(&mut network, layer_id, 0.0).unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 1.0).unwrap();
+    ///
+    /// // the hidden node has no inputs, so nothing else feeds the output
+    /// let feeding = network.feeding_into(output_node_id).unwrap();
+    /// assert_eq!(feeding.into_iter().collect::<Vec<usize>>(), vec![hidden_node_id]);
+    /// ```
+    pub fn feeding_into(&self, node_id: usize) -> Result<HashSet<usize>> {
+        self.walk(node_id, |edge| (edge.node_to_id, edge.node_from_id))
+    }
+
+    /// Count the distinct paths from one node to another that follow enabled,
+    /// non-recurrent edges forwards. A node has one path to itself, and the
+    /// count is 0 if there is no path or either node does not exist. Counts
+    /// too large for a `usize` saturate.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::ReLU).unwrap();
+    /// let layer_id = network.add_layer();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let hidden_node_id = Node::create(&mut network, layer_id, 0.0).unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, hidden_node_id, 1.0).unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 1.0).unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, 1.0).unwrap();
+    ///
+    /// assert_eq!(network.count_paths(input_node_id, output_node_id), 2);
+    /// assert_eq!(network.count_paths(output_node_id, input_node_id), 0);
+    /// ```
+    pub fn count_paths(&self, from: usize, to: usize) -> usize {
+        if self.get_node(from).is_none() || self.get_node(to).is_none() {
+            return 0;
+        }
+
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| !edge.recurrent && edge.enabled)
+        {
+            next.entry(edge.node_from_id)
+                .or_default()
+                .push(edge.node_to_id);
+        }
+
+        // every edge points forwards in this order, so each node's count is
+        // final before it is passed on
+        let mut counts = HashMap::from([(from, 1usize)]);
+
+        for node_id in self.topological_node_ids() {
+            if node_id == to {
+                break;
+            }
+
+            let count = match counts.get(&node_id) {
+                Some(count) => *count,
+                None => continue,
+            };
+
+            for next_id in next.get(&node_id).into_iter().flatten() {
+                let next_count = counts.entry(*next_id).or_insert(0);
+                *next_count = next_count.saturating_add(count);
+            }
+        }
+
+        counts.get(&to).copied().unwrap_or(0)
+    }
+
+    /// Depth-first search from a node along enabled edges, where `direction`
+    /// maps each edge to the `(from, to)` pair to follow.
+    fn walk(
+        &self,
+        node_id: usize,
+        direction: impl Fn(&Edge) -> (usize, usize),
+    ) -> Result<HashSet<usize>> {
+        ensure!(
+            self.get_node(node_id).is_some(),
+            "Node with id {} does not exist",
+            node_id
+        );
+
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        for edge in self.edges.iter().filter(|edge| edge.enabled) {
+            let (from, to) = direction(edge);
+            next.entry(from).or_default().push(to);
+        }
+
+        let mut visited = HashSet::new();
+        let mut stack = vec![node_id];
+
+        while let Some(id) = stack.pop() {
+            for next_id in next.get(&id).into_iter().flatten() {
+                if visited.insert(*next_id) {
+                    stack.push(*next_id);
+                }
+            }
+        }
+
+        Ok(visited)
+    }
+
+    /// Whether every output node can be reached from at least one input node
+    /// along enabled, non-recurrent edges. An output that no input reaches
+    /// only ever outputs its bias, which usually means the network is broken.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 2, ActivationFn::ReLU).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_ids = network.output_node_ids();
+    ///
+    /// Edge::create(&mut network, input_node_id, output_node_ids[0], 1.0).unwrap();
+    /// assert!(!network.is_fully_connected());
+    ///
+    /// Edge::create(&mut network, input_node_id, output_node_ids[1], 1.0).unwrap();
+    /// assert!(network.is_fully_connected());
+    /// ```
+    pub fn is_fully_connected(&self) -> bool {
+        self.connected_outputs() == self.output_size()
+    }
+
+    /// The number of output nodes reachable from an input node along enabled,
+    /// non-recurrent edges.
+    fn connected_outputs(&self) -> usize {
+        self.output_size() - self.disconnected_output_ids().len()
+    }
+
+    /// The ids of the input nodes and every node they reach along enabled,
+    /// non-recurrent edges.
+    fn reachable_from_inputs(&self) -> HashSet<usize> {
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| !edge.recurrent && edge.enabled)
+        {
+            next.entry(edge.node_from_id)
+                .or_default()
+                .push(edge.node_to_id);
+        }
+
+        let mut stack = self.input_node_ids();
+        let mut reached = stack.iter().copied().collect::<HashSet<usize>>();
+
+        while let Some(node_id) = stack.pop() {
+            for next_id in next.get(&node_id).into_iter().flatten() {
+                if reached.insert(*next_id) {
+                    stack.push(*next_id);
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// The ids of the output nodes that no input node can reach along
+    /// enabled, non-recurrent edges, sorted by id.
+    pub(crate) fn disconnected_output_ids(&self) -> Vec<usize> {
+        let reached = self.reachable_from_inputs();
+
+        self.output_node_ids()
+            .into_iter()
+            .filter(|id| !reached.contains(id))
+            .collect()
+    }
+
+    /// Compare this network to `other`, matching nodes and edges by id. An
+    /// edge whose id is in both networks but connects different nodes is
+    /// reported as removed and added. Weights and biases are compared
+    /// exactly; activation functions and enabled flags are not compared.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// let mut child = network.clone();
+    /// let node_id = child.split_edge(1).unwrap();
+    ///
+    /// let diff = network.diff(&child);
+    /// assert_eq!(diff.added_nodes, vec![node_id]);
+    /// assert_eq!(diff.added_edges.len(), 2);
+    ///
+    /// // the split edge is kept, disabled
+    /// assert!(diff.removed_edges.is_empty());
+    /// assert_eq!(child.is_edge_enabled(1), Some(false));
+    /// ```
+    pub fn diff(&self, other: &Network) -> NetworkDiff {
+        let mut diff = NetworkDiff::default();
+
+        for node in self.nodes.iter() {
+            match other.get_node(node.id) {
+                Some(other_node) if other_node.bias != node.bias => {
+                    diff.changed_biases
+                        .push((node.id, node.bias, other_node.bias));
+                }
+                Some(_) => {}
+                None => diff.removed_nodes.push(node.id),
+            }
+        }
+
+        for node in other.nodes.iter() {
+            if self.get_node(node.id).is_none() {
+                diff.added_nodes.push(node.id);
+            }
+        }
+
+        let same_endpoints = |edge: &Edge, other_edge: &Edge| {
+            edge.node_from_id == other_edge.node_from_id && edge.node_to_id == other_edge.node_to_id
+        };
+
+        for edge in self.edges.iter() {
+            match other.get_edge(edge.id) {
+                Some(other_edge) if same_endpoints(edge, other_edge) => {
+                    if other_edge.weight != edge.weight {
+                        diff.changed_weights
+                            .push((edge.id, edge.weight, other_edge.weight));
+                    }
+                }
+                _ => diff.removed_edges.push(edge.id),
+            }
+        }
+
+        for edge in other.edges.iter() {
+            match self.get_edge(edge.id) {
+                Some(self_edge) if same_endpoints(self_edge, edge) => {}
+                _ => diff.added_edges.push(edge.id),
+            }
+        }
+
+        diff.added_nodes.sort();
+        diff.removed_nodes.sort();
+        diff.added_edges.sort();
+        diff.removed_edges.sort();
+        diff.changed_weights.sort_by_key(|(id, _, _)| *id);
+        diff.changed_biases.sort_by_key(|(id, _, _)| *id);
+
+        diff
+    }
+
+    /// Imports the nodes and edges of `other` into this network, giving them
+    /// fresh ids. Every layer of `other`, including its input and output
+    /// layers, becomes a new hidden layer placed after the existing hidden
+    /// layers, so `other`'s inputs and outputs become hidden nodes. As hidden
+    /// nodes, `other`'s input nodes now apply their bias and activation
+    /// function. Enabled and frozen flags are kept. The imported nodes are
+    /// not connected to the rest of the network.
+    ///
+    /// Returns a map from the ids of `other`'s nodes to their new ids.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let other = Network::feedforward(&[1, 2, 1], ActivationFn::ReLU).unwrap();
+    ///
+    /// let ids = network.merge(&other).unwrap();
+    ///
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, ids[&other.input_node_ids()[0]], 1.0).unwrap();
+    /// Edge::create(&mut network, ids[&other.output_node_ids()[0]], output_node_id, 1.0).unwrap();
+    /// ```
+    pub fn merge(&mut self, other: &Network) -> Result<HashMap<usize, usize>> {
+        let mut other_layers = other.layers.clone();
+        other_layers.sort();
+
+        let layer_ids = other_layers
+            .iter()
+            .map(|other_layer| (*other_layer, self.add_layer()))
+            .collect::<Vec<(LayerID, LayerID)>>();
+
+        let mut node_ids = HashMap::new();
+
+        // adding nodes in order of id keeps lateral edges pointing towards
+        // higher ids
+        let mut other_nodes = other.nodes.iter().collect::<Vec<&Node>>();
+        other_nodes.sort_by_key(|node| node.id);
+
+        for node in other_nodes {
+            let (_, layer_id) = layer_ids
+                .iter()
+                .find(|(other_layer, _)| *other_layer == node.layer_id)
+                .with_context(|| format!("Layer of node {} does not exist", node.id))?;
+
+            let id = if node.node_type == NodeType::BiasNode {
+                self.add_bias_node(*layer_id)?
+            } else {
+                Node::create_with_custom_activation(self, *layer_id, node.bias, node.activation_fn)?
+            };
+
+            let new_node = self
+                .get_node_mut(id)
+                .context("Merged node does not exist")?;
+            new_node.frozen = node.frozen;
+            new_node.activation_closure = node.activation_closure.clone();
+            node_ids.insert(node.id, id);
+        }
+
+        for edge in other.edges.iter() {
+            let node_from_id = *node_ids
+                .get(&edge.node_from_id)
+                .context("Node from does not exist")?;
+            let node_to_id = *node_ids
+                .get(&edge.node_to_id)
+                .context("Node to does not exist")?;
+
+            let edge_id = if edge.recurrent {
+                Edge::create_recurrent(self, node_from_id, node_to_id, edge.weight)?
+            } else if edge.lateral {
+                Edge::create_lateral(self, node_from_id, node_to_id, edge.weight)?
+            } else {
+                Edge::create(self, node_from_id, node_to_id, edge.weight)?
+            };
+
+            self.set_edge_enabled(edge_id, edge.enabled)?;
+
+            if let Some(new_edge) = self.edges.iter_mut().find(|e| e.id == edge_id) {
+                new_edge.frozen = edge.frozen;
+            }
+        }
+
+        Ok(node_ids)
+    }
+
+    /// Joins two networks into one that fires as `second` applied to the
+    /// outputs of `first`. `first`'s output nodes become hidden nodes, each
+    /// connected with a weight of 1 to the matching former input node of
+    /// `second`, which keeps its activation function and has its bias set to 0.
+    /// Like `merge`, every layer of `second` except its output layer becomes a
+    /// new hidden layer.
+    ///
+    /// `first` must have as many outputs as `second` has inputs, and must not
+    /// use softmax outputs or an output transform, since its outputs are no
+    /// longer read. The result keeps `second`'s softmax and output transform.
+    /// Both networks must have the same pre-activation clamp, as it applies to
+    /// the whole network. Input nodes are never clamped, but as hidden nodes
+    /// `second`'s former inputs are, so with a clamp the result differs from
+    /// firing `first` and then `second` whenever `first`'s outputs fall
+    /// outside the clamp range. The result has no fitness.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let features = Network::feedforward(&[4, 8, 3], ActivationFn::ReLU).unwrap();
+    /// let classifier = Network::feedforward(&[3, 2], ActivationFn::Sigmoid).unwrap();
+    ///
+    /// let mut model = Network::chain(features.clone(), classifier.clone()).unwrap();
+    /// assert_eq!(model.input_size(), 4);
+    /// assert_eq!(model.output_size(), 2);
+    ///
+    /// let mut output = vec![];
+    /// model.fire(vec![0.1, 0.2, 0.3, 0.4], &mut output).unwrap();
+    /// ```
+    pub fn chain(first: Network, second: Network) -> Result<Network> {
+        ensure!(
+            first.output_size() == second.input_size(),
+            "The first network has {} outputs, but the second has {} inputs",
+            first.output_size(),
+            second.input_size()
+        );
+        ensure!(
+            !first.softmax_output && first.output_transform.is_none(),
+            "The outputs of the first network must not have a softmax or output transform"
+        );
+        ensure!(
+            first.preactivation_clamp == second.preactivation_clamp,
+            "Both networks must have the same pre-activation clamp"
+        );
+
+        let mut network = first;
+        let first_output_ids = network.output_node_ids();
+        let first_output_fn = network.layer_activation_fn(LayerID::OutputLayer);
+
+        // the first network's outputs move to a hidden layer after its others
+        let joint_layer = network.add_layer();
+
+        for node in network
+            .nodes
+            .iter_mut()
+            .filter(|node| node.node_type == NodeType::OutputNode)
+        {
+            node.node_type = NodeType::HiddenNode;
+            node.layer_id = joint_layer;
+        }
+
+        let ids = network.merge(&second)?;
+
+        // merge turns the second network's output layer into the last hidden
+        // layer, which becomes the output layer again
+        let last_layer = network.last_hidden_or_input_layer();
+        network.layers.retain(|layer_id| *layer_id != last_layer);
+
+        for node in network
+            .nodes
+            .iter_mut()
+            .filter(|node| node.layer_id == last_layer)
+        {
+            node.node_type = NodeType::OutputNode;
+            node.layer_id = LayerID::OutputLayer;
+        }
+
+        network
+            .layer_activation_fns
+            .retain(|(layer_id, _)| *layer_id != LayerID::OutputLayer);
+        network
+            .layer_activation_fns
+            .push((joint_layer, first_output_fn));
+        network.layer_activation_fns.push((
+            LayerID::OutputLayer,
+            second.layer_activation_fn(LayerID::OutputLayer),
+        ));
+
+        for (first_output_id, second_input_id) in
+            first_output_ids.into_iter().zip(second.input_node_ids())
+        {
+            let node_to_id = ids[&second_input_id];
+            network
+                .get_node_mut(node_to_id)
+                .context("Node to does not exist")?
+                .bias = 0.0;
+
+            Edge::create(&mut network, first_output_id, node_to_id, 1.0)?;
+        }
+
+        network.softmax_output = second.softmax_output;
+        network.output_transform = second.output_transform;
+        network.fitness = None;
+        network.invalidate_cache();
+
+        Ok(network)
+    }
+
+    /// Get a Graphviz DOT representation of the network. Each layer is drawn
+    /// as a cluster, nodes are labeled with their id, bias and activation
+    /// function and colored by node type, and edges are labeled with their
+    /// weight. Recurrent edges are dashed and disabled edges are dotted.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 2, 1], ActivationFn::Sigmoid).unwrap();
+    ///
+    /// // render with `dot -Tpng network.dot -o network.png`
+    /// let dot = network.to_dot();
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut layers = self.layers.clone();
+        layers.sort();
+
+        let mut dot = String::new();
+        dot.push_str("digraph network {\n    rankdir=LR;\n");
+
+        for (index, layer_id) in layers.iter().enumerate() {
+            let _ = writeln!(dot, "    subgraph cluster_{} {{", index);
+            let _ = writeln!(dot, "        label=\"{:?}\";", layer_id);
+            dot.push_str("        rank=same;\n");
+
+            let mut nodes = self
+                .nodes
+                .iter()
+                .filter(|node| node.layer_id == *layer_id)
+                .collect::<Vec<&Node>>();
+            nodes.sort_by_key(|node| node.id);
+
+            for node in nodes {
+                let color = match node.node_type {
+                    NodeType::InputNode => "lightblue",
+                    NodeType::HiddenNode => "lightgray",
+                    NodeType::OutputNode => "lightgreen",
+                    NodeType::BiasNode => "khaki",
+                };
+
+                let activation: &dyn fmt::Debug = match &node.activation_closure {
+                    Some(activation_closure) => activation_closure,
+                    None => &node.activation_fn,
+                };
+
+                let _ = writeln!(
+                    dot,
+                    "        n{} [label=\"{}\\nbias: {}\\n{:?}\", style=filled, fillcolor={}];",
+                    node.id, node.id, node.bias, activation, color
+                );
+            }
+
+            dot.push_str("    }\n");
+        }
+
+        let mut edges = self.edges.iter().collect::<Vec<&Edge>>();
+        edges.sort_by_key(|edge| edge.id);
+
+        for edge in edges {
+            let style = if !edge.enabled {
+                ", style=dotted"
+            } else if edge.recurrent {
+                ", style=dashed"
+            } else {
+                ""
+            };
+
+            let _ = writeln!(
+                dot,
+                "    n{} -> n{} [label=\"{}\"{}];",
+                edge.node_from_id, edge.node_to_id, edge.weight, style
+            );
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Create a fixed-point copy of the network for integer inference, with
+    /// every weight and bias multiplied by `scale`. See `QuantizedNetwork` for
+    /// the precision tradeoffs. The softmax of a network made with
+    /// `create_classifier` is not applied, so the quantized network returns
+    /// the logits instead of probabilities. Neither is the output transform.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 2, 1], ActivationFn::ReLU).unwrap();
+    /// let quantized = network.quantize(1024.0).unwrap();
+    ///
+    /// let output = quantized.fire(&[0, 1024]).unwrap();
+    /// ```
+    pub fn quantize(&self, scale: Float) -> Result<QuantizedNetwork> {
+        QuantizedNetwork::new(self, scale)
+    }
+
+    /// Serialize the network to a string
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// # let layerid = network.add_layer();
+    /// # let input_node_id = network.input_node_ids().pop().unwrap();
+    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
+    /// # let output_node_id = network.output_node_ids().pop().unwrap();
+    /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
+    /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
+    /// let string = network.serialize().unwrap();
+    /// ```
+    pub fn serialize(&self) -> Result<String> {
+        serde_json::to_string(self).context("Could not serialize network")
+    }
+
+    /// Serialize the network to a string with its nodes and edges sorted by
+    /// id, and its layers in order. The order of the internal vecs depends
+    /// on the history of edits, so two equal networks may `serialize`
+    /// differently; their canonical forms are byte-identical. This makes
+    /// saved networks reproducible and easy to diff.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::ReLU).unwrap();
+    /// let input_node_ids = network.input_node_ids();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_ids[0], output_node_id, 0.5).unwrap();
+    /// Edge::create(&mut network, input_node_ids[1], output_node_id, -1.5).unwrap();
+    ///
+    /// let string = network.serialize_canonical().unwrap();
+    /// let loaded = Network::deserialized(&string).unwrap();
+    /// assert_eq!(loaded.serialize_canonical().unwrap(), string);
+    /// ```
+    pub fn serialize_canonical(&self) -> Result<String> {
+        let mut network = self.clone();
+
+        network.nodes.sort_by_key(|node| node.id);
+        network.edges.sort_by_key(|edge| edge.id);
+        network.layers.sort();
+        network
+            .layer_activation_fns
+            .sort_by_key(|(layer_id, _)| *layer_id);
+
+        network.serialize()
+    }
+
+    /// Deserialize a network from a string. Networks saved by older versions
+    /// of nnrs are upgraded, and networks saved in a newer format than this
+    /// version supports return an error.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// # let layer_id = network.add_layer();
+    /// # let input_node_id = network.input_node_ids().pop().unwrap();
+    /// # let hidden_node_id = Node::create(&mut network, LayerID::HiddenLayer(0), 0.0).unwrap();
+    /// # let output_node_id = network.output_node_ids().pop().unwrap();
+    /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
+    /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
+    /// let string = network.serialize().unwrap();
+    /// let mut network2 = Network::deserialized(&string).unwrap();
+    ///
+    /// let mut outs = Vec::new();
+    /// let mut outs2 = Vec::new();
+    ///
+    /// network.fire(vec![0.8], &mut outs).unwrap();
+    /// network2.fire(vec![0.8], &mut outs2).unwrap();
+    ///
+    /// assert_eq!(outs, outs2);
+    /// ```
+    pub fn deserialized(string: &str) -> Result<Self> {
+        // keep serde's message, which explains migration errors, at the top
+        serde_json::from_str(string)
+            .map_err(|error| anyhow!("Could not deserialize network: {}", error))
+    }
+
+    /// Serialize the network to a file
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// # let layerid = network.add_layer();
+    /// # let input_node_id = network.input_node_ids().pop().unwrap();
+    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
+    /// # let output_node_id = network.output_node_ids().pop().unwrap();
+    /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
+    /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
+    /// network.save("network.json").unwrap();
+    /// ```
+    pub fn save(&self, path: &str) -> Result<()> {
+        let mut file = File::create(path).context("Could not create file")?;
+        file.write_all(self.serialize()?.as_bytes())
+            .context("Could not write to file")?;
+        Ok(())
+    }
+
+    /// Deserialize a network from a file
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// # let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// # let layerid = network.add_layer();
+    /// # let input_node_id = network.input_node_ids().pop().unwrap();
+    /// # let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
+    /// # let output_node_id = network.output_node_ids().pop().unwrap();
+    /// # Edge::create(&mut network, input_node_id, hidden_node_id, 0.5).unwrap();
+    /// # Edge::create(&mut network, hidden_node_id, output_node_id, 0.5).unwrap();
+    /// network.save("network.json").unwrap();
+    /// let mut network2 = Network::load("network.json").unwrap();
+    ///
+    /// let mut outs = Vec::new();
+    /// let mut outs2 = Vec::new();
+    ///
+    /// network.fire(vec![0.8], &mut outs).unwrap();
+    /// network2.fire(vec![0.8], &mut outs2).unwrap();
+    ///
+    /// assert_eq!(outs, outs2);
+    /// ```
+    pub fn load(path: &str) -> Result<Self> {
+        let mut file = File::open(path).context("Could not open file")?;
+        let mut string = String::new();
+        file.read_to_string(&mut string)
+            .context("Could not read file")?;
+        Self::deserialized(&string)
+    }
+
+    /// Create a new network with the given number of inputs and outputs.
+    /// Input nodes use `Linear` activation, so they pass their inputs on
+    /// unchanged. Every other node uses `activation_fn` unless its layer is
+    /// given another one with `set_layer_activation`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// ```
+    pub fn create(input_ct: usize, output_ct: usize, activation_fn: ActivationFn) -> Result<Self> {
+        Self::with_capacity(input_ct, output_ct, activation_fn, 0, 0)
+    }
+
+    /// Create a new network like `create`, with room for at least
+    /// `node_capacity` nodes and `edge_capacity` edges before reallocating.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::with_capacity(2, 1, ActivationFn::Linear, 16, 64).unwrap();
+    /// ```
+    pub fn with_capacity(
+        input_ct: usize,
+        output_ct: usize,
+        activation_fn: ActivationFn,
+        node_capacity: usize,
+        edge_capacity: usize,
+    ) -> Result<Self> {
+        let mut network = Self {
+            edges: Vec::with_capacity(edge_capacity),
+            nodes: Vec::with_capacity(node_capacity.max(input_ct + output_ct)),
+            layers: vec![LayerID::InputLayer, LayerID::OutputLayer],
+            fitness: None,
+            activation_fn,
+            layer_activation_fns: vec![(LayerID::InputLayer, ActivationFn::Linear)],
+            next_node_id: 1,
+            next_edge_id: 1,
+            softmax_output: false,
+            preactivation_clamp: None,
+            output_transform: None,
+            strict: false,
+            cache: None,
+        };
+
+        let mut input_ids = Vec::new();
+        let mut output_ids = Vec::new();
+
+        for _ in 0..input_ct {
+            input_ids.push(Node::create(&mut network, LayerID::InputLayer, 0.0)?);
+        }
+
+        for _ in 0..output_ct {
+            output_ids.push(Node::create(&mut network, LayerID::OutputLayer, 0.0)?);
+        }
+
+        Ok(network)
+    }
+
+    /// Create a new network for classifying inputs into `num_classes` classes.
+    /// The output nodes use `Linear` activation, and `fire` applies softmax to
+    /// the outputs, so they are probabilities that sum to 1. Nodes added to
+    /// hidden layers use `hidden_activation`. Like `create`, the network
+    /// starts without any edges.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, activationfn::ActivationFn, weightinit::WeightInit, Float};
+    /// let mut network = Network::create_classifier(2, 3, ActivationFn::ReLU).unwrap();
+    /// let layer_id = network.add_layer();
+    /// for _ in 0..4 {
+    ///     Node::create(&mut network, layer_id, 0.0).unwrap();
+    /// }
+    ///
+    /// network.connect_layers(LayerID::InputLayer, layer_id, WeightInit::He).unwrap();
+    /// network.connect_layers(layer_id, LayerID::OutputLayer, WeightInit::Xavier).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.fire(vec![0.5, -1.0], &mut output).unwrap();
+    ///
+    /// assert!((output.iter().sum::<Float>() - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn create_classifier(
+        input_ct: usize,
+        num_classes: usize,
+        hidden_activation: ActivationFn,
+    ) -> Result<Self> {
+        let mut network = Self::create(input_ct, num_classes, hidden_activation)?;
+        network.set_layer_activation(LayerID::OutputLayer, ActivationFn::Linear)?;
+        network.softmax_output = true;
+
+        Ok(network)
+    }
+
+    /// Create a fully-connected feedforward network. The first element of
+    /// `layer_sizes` is the number of inputs, the last is the number of outputs,
+    /// and every element in between adds a hidden layer of that size. Every
+    /// node is connected to every node in the next layer with a random weight
+    /// between -1 and 1.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::Sigmoid).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.fire(vec![0.0, 1.0], &mut output).unwrap();
+    /// ```
+    pub fn feedforward(layer_sizes: &[usize], activation_fn: ActivationFn) -> Result<Self> {
+        ensure!(
+            layer_sizes.len() >= 2,
+            "A feedforward network needs at least an input and an output layer"
+        );
+
+        let mut network = Self::create(
+            layer_sizes[0],
+            layer_sizes[layer_sizes.len() - 1],
+            activation_fn,
+        )?;
+
+        for size in &layer_sizes[1..layer_sizes.len() - 1] {
+            network.push_dense(LayerSpec {
+                size: *size,
+                activation: activation_fn,
+            })?;
+        }
+
+        let last_layer = network.last_hidden_or_input_layer();
+        network.connect_layers(last_layer, LayerID::OutputLayer, WeightInit::Uniform(1.0))?;
+
+        Ok(network)
+    }
+
+    /// Create a network with randomly chosen edges. `hidden_layers` hidden
+    /// layers of `nodes_per_layer` nodes each are added between the input and
+    /// output layers. Every pair of nodes where the first is in an earlier
+    /// layer is then connected with probability `edge_probability`, with a
+    /// random weight between -1 and 1. If an output cannot be reached from
+    /// any input afterwards, a chain of edges from a random input through one
+    /// random node of each hidden layer is added, so every output depends on
+    /// at least one input.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut rng = rand::thread_rng();
+    /// let mut network = Network::random(3, 2, 2, 4, 0.5, ActivationFn::Tanh, &mut rng).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.fire(vec![0.0, 1.0, -1.0], &mut output).unwrap();
+    /// ```
+    pub fn random(
+        input_ct: usize,
+        output_ct: usize,
+        hidden_layers: usize,
+        nodes_per_layer: usize,
+        edge_probability: Float,
+        activation_fn: ActivationFn,
+        rng: &mut impl Rng,
+    ) -> Result<Self> {
+        ensure!(
+            (0.0..=1.0).contains(&edge_probability),
+            "Edge probability must be between 0 and 1"
+        );
+
+        ensure!(
+            input_ct > 0 && output_ct > 0,
+            "A random network needs at least one input and one output"
+        );
+
+        ensure!(
+            hidden_layers == 0 || nodes_per_layer > 0,
+            "Hidden layers need at least one node"
+        );
+
+        let mut network = Self::create(input_ct, output_ct, activation_fn)?;
+
+        for _ in 0..hidden_layers {
+            let layer_id = network.add_layer();
+
+            for _ in 0..nodes_per_layer {
+                Node::create(&mut network, layer_id, 0.0)?;
+            }
+        }
+
+        let mut layer_ids = network.layers.clone();
+        layer_ids.sort();
+
+        let layers = layer_ids
+            .iter()
+            .map(|layer_id| network.layer_node_ids(*layer_id))
+            .collect::<Result<Vec<Vec<usize>>>>()?;
+
+        for (index, node_from_ids) in layers.iter().enumerate() {
+            for node_to_ids in layers[index + 1..].iter() {
+                for node_from_id in node_from_ids {
+                    for node_to_id in node_to_ids {
+                        if rng.gen::<Float>() < edge_probability {
+                            let weight = rng.gen_range(-1.0..=1.0);
+                            Edge::create(&mut network, *node_from_id, *node_to_id, weight)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        for output_node_id in network.output_node_ids() {
+            if layers[0]
+                .iter()
+                .any(|id| network.count_paths(*id, output_node_id) > 0)
+            {
+                continue;
+            }
+
+            let mut chain = layers[..layers.len() - 1]
+                .iter()
+                .map(|node_ids| node_ids[rng.gen_range(0..node_ids.len())])
+                .collect::<Vec<usize>>();
+            chain.push(output_node_id);
+
+            for pair in chain.windows(2) {
+                if network
+                    .edges
+                    .iter()
+                    .any(|e| e.node_from_id == pair[0] && e.node_to_id == pair[1])
+                {
+                    continue;
+                }
+
+                let weight = rng.gen_range(-1.0..=1.0);
+                Edge::create(&mut network, pair[0], pair[1], weight)?;
+            }
+        }
+
+        Ok(network)
+    }
+
+    /// Adds a hidden layer of `spec.size` nodes using `spec.activation`, and
+    /// connects every node in the previous layer to every new node with a
+    /// random weight between -1 and 1. The previous layer is the last hidden
+    /// layer, or the input layer if there are no hidden layers yet. The new
+    /// layer is not connected to the output layer.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, layer::LayerSpec, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Sigmoid).unwrap();
+    ///
+    /// let first = network.push_dense(LayerSpec { size: 4, activation: ActivationFn::ReLU }).unwrap();
+    /// let second = network.push_dense(LayerSpec { size: 3, activation: ActivationFn::ReLU }).unwrap();
+    ///
+    /// assert_eq!(network.layer_node_ids(second).unwrap().len(), 3);
+    /// ```
+    pub fn push_dense(&mut self, spec: LayerSpec) -> Result<LayerID> {
+        let previous_layer = self.last_hidden_or_input_layer();
+        let node_from_ids = self.layer_node_ids(previous_layer)?;
+
+        let layer_id = self.add_layer();
+        self.set_layer_activation(layer_id, spec.activation)?;
+
+        let mut node_to_ids = Vec::with_capacity(spec.size);
+
+        for _ in 0..spec.size {
+            node_to_ids.push(Node::create(self, layer_id, 0.0)?);
+        }
+
+        self.connect_nodes(&node_from_ids, &node_to_ids, WeightInit::Uniform(1.0))?;
+
+        Ok(layer_id)
+    }
+
+    /// Connects every node in the `from` layer to every node in the `to`
+    /// layer, skipping pairs that are already connected and bias nodes in
+    /// `to`. Returns the ids of the created edges.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, activationfn::ActivationFn, weightinit::WeightInit};
+    /// let mut network = Network::create(2, 1, ActivationFn::ReLU).unwrap();
+    /// let layer_id = network.add_layer();
+    /// for _ in 0..3 {
+    ///     Node::create(&mut network, layer_id, 0.0).unwrap();
+    /// }
+    ///
+    /// let edge_ids = network.connect_layers(LayerID::InputLayer, layer_id, WeightInit::He).unwrap();
+    /// assert_eq!(edge_ids.len(), 6);
+    ///
+    /// // the output layer comes after the hidden layer, not before
+    /// assert!(network.connect_layers(LayerID::OutputLayer, layer_id, WeightInit::Xavier).is_err());
+    /// ```
+    pub fn connect_layers(
+        &mut self,
+        from: LayerID,
+        to: LayerID,
+        init: WeightInit,
+    ) -> Result<Vec<usize>> {
+        ensure!(to > from, "Layer {:?} must come after layer {:?}", to, from);
+
+        let node_from_ids = self.layer_node_ids(from)?;
+        let mut node_to_ids = self
+            .get_layer(to)
+            .with_context(|| format!("Layer with id {:?} does not exist", to))?
+            .iter()
+            .filter(|node| node.node_type != NodeType::BiasNode)
+            .map(|node| node.id)
+            .collect::<Vec<usize>>();
+        node_to_ids.sort();
+
+        self.connect_nodes(&node_from_ids, &node_to_ids, init)
+    }
+
+    fn last_hidden_or_input_layer(&self) -> LayerID {
+        self.layers
+            .iter()
+            .filter(|layer| **layer != LayerID::OutputLayer)
+            .max()
+            .copied()
+            .unwrap_or(LayerID::InputLayer)
+    }
+
+    fn connect_nodes(
+        &mut self,
+        node_from_ids: &[usize],
+        node_to_ids: &[usize],
+        init: WeightInit,
+    ) -> Result<Vec<usize>> {
+        let mut rng = rand::thread_rng();
+        let mut edge_ids = Vec::new();
+
+        for node_from_id in node_from_ids {
+            for node_to_id in node_to_ids {
+                if self
+                    .edges
+                    .iter()
+                    .any(|e| e.node_from_id == *node_from_id && e.node_to_id == *node_to_id)
+                {
+                    continue;
+                }
+
+                let weight = init.sample(&mut rng, node_from_ids.len(), node_to_ids.len());
+                edge_ids.push(Edge::create(self, *node_from_id, *node_to_id, weight)?);
+            }
+        }
+
+        Ok(edge_ids)
+    }
+
+    /// Get the ids of all the input nodes, sorted by id. This is the order in
+    /// which `fire` assigns its inputs.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    pub fn input_node_ids(&self) -> Vec<usize> {
+        let mut ids = self
+            .nodes
+            .iter()
+            .filter(|n| n.node_type == NodeType::InputNode)
+            .map(|n| n.id)
+            .collect::<Vec<usize>>();
+
+        ids.sort();
+        ids
+    }
+
+    /// Get the ids of all the output nodes, sorted by id. This is the order in
+    /// which `fire` returns its outputs.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// ```
+    pub fn output_node_ids(&self) -> Vec<usize> {
+        let mut ids = self
+            .nodes
+            .iter()
+            .filter(|n| n.layer_id == LayerID::OutputLayer)
+            .map(|n| n.id)
+            .collect::<Vec<usize>>();
+
+        ids.sort();
+        ids
+    }
+
+    /// Get the fitness of the network, if one has been set
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// assert_eq!(network.fitness(), None);
+    ///
+    /// network.set_fitness(0.5);
+    /// assert_eq!(network.fitness(), Some(0.5));
+    ///
+    /// network.clear_fitness();
+    /// assert_eq!(network.fitness(), None);
+    /// ```
+    pub fn fitness(&self) -> Option<f64> {
+        self.fitness
+    }
+
+    /// Set the fitness of the network
+    pub fn set_fitness(&mut self, fitness: f64) {
+        self.fitness = Some(fitness);
+    }
+
+    /// Clear the fitness of the network, for example after loading a network
+    /// that will be evaluated on a different task
+    pub fn clear_fitness(&mut self) {
+        self.fitness = None;
+    }
+
+    /// Get the number of input nodes
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::create(3, 1, ActivationFn::Linear).unwrap();
+    /// assert_eq!(network.input_size(), 3);
+    /// ```
+    pub fn input_size(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|n| n.node_type == NodeType::InputNode)
+            .count()
+    }
+
+    /// Get the number of output nodes
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::create(3, 1, ActivationFn::Linear).unwrap();
+    /// assert_eq!(network.output_size(), 1);
+    /// ```
+    pub fn output_size(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|n| n.layer_id == LayerID::OutputLayer)
+            .count()
+    }
+
+    /// Get the ids of all the nodes in a layer
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let layer_id = network.add_layer();
+    /// let hidden_node_id = Node::create(&mut network, layer_id, 0.0).unwrap();
+    ///
+    /// assert_eq!(network.layer_node_ids(layer_id).unwrap(), vec![hidden_node_id]);
+    /// assert!(network.layer_node_ids(LayerID::HiddenLayer(1)).is_err());
+    /// ```
+    pub fn layer_node_ids(&self, layer_id: LayerID) -> Result<Vec<usize>> {
+        Ok(self
+            .get_layer(layer_id)
+            .with_context(|| format!("Layer with id {:?} does not exist", layer_id))?
+            .iter()
+            .map(|n| n.id)
+            .collect())
+    }
+
+    /// Get the weights between two layers as a dense matrix, with one row per
+    /// node in `to` and one column per node in `from`, both ordered by node
+    /// id. `matrix[i][j]` is the weight of the edge from the `j`th node of
+    /// `from` to the `i`th node of `to`, or 0 if there is no such edge or it
+    /// is disabled. Recurrent edges are left out. Bias nodes are included like
+    /// any other node.
+    ///
+    /// With `layer_biases`, this gives a fully-connected layer's parameters in
+    /// the usual `W * x + b` form, for doing the math with another library.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, layer::LayerID, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_ids = network.input_node_ids();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_ids[1], output_node_id, 0.5).unwrap();
+    ///
+    /// let weights = network.layer_weights(LayerID::InputLayer, LayerID::OutputLayer).unwrap();
+    /// assert_eq!(weights, vec![vec![0.0, 0.5]]);
+    /// assert_eq!(network.layer_biases(LayerID::OutputLayer).unwrap(), vec![0.0]);
+    /// ```
+    pub fn layer_weights(&self, from: LayerID, to: LayerID) -> Result<Vec<Vec<Float>>> {
+        let mut from_ids = self.layer_node_ids(from)?;
+        let mut to_ids = self.layer_node_ids(to)?;
+        from_ids.sort();
+        to_ids.sort();
+
+        let mut matrix = vec![vec![0.0; from_ids.len()]; to_ids.len()];
+
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| edge.enabled && !edge.recurrent)
+        {
+            let column = from_ids.iter().position(|id| *id == edge.node_from_id);
+            let row = to_ids.iter().position(|id| *id == edge.node_to_id);
+
+            if let (Some(row), Some(column)) = (row, column) {
+                matrix[row][column] = edge.weight;
+            }
+        }
+
+        Ok(matrix)
+    }
+
+    /// Get the biases of the nodes in a layer, ordered by node id like the
+    /// rows of `layer_weights`. Input nodes and bias nodes do not use their
+    /// bias, but it is still returned.
+    pub fn layer_biases(&self, layer_id: LayerID) -> Result<Vec<Float>> {
+        let mut nodes = self
+            .get_layer(layer_id)
+            .with_context(|| format!("Layer with id {:?} does not exist", layer_id))?;
+        nodes.sort_by_key(|node| node.id);
+
+        Ok(nodes.iter().map(|node| node.bias).collect())
+    }
+
+    /// Get the number of edges on the longest path from any input node to any
+    /// output node. Edges can skip layers, so this may be less than the number
+    /// of layers. Recurrent and disabled edges are ignored. Returns 0 if no
+    /// output node can be reached from an input node.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 3, 3, 1], ActivationFn::ReLU).unwrap();
+    /// assert_eq!(network.depth(), 3);
+    /// ```
+    pub fn depth(&self) -> usize {
+        // longest distance from any input node, if the node is reachable
+        let mut distances: HashMap<usize, usize> = self
+            .input_node_ids()
+            .into_iter()
+            .map(|id| (id, 0))
+            .collect();
+
+        for node_id in self.topological_node_ids() {
+            let distance = self
+                .edges
+                .iter()
+                .filter(|edge| edge.node_to_id == node_id && !edge.recurrent && edge.enabled)
+                .filter_map(|edge| distances.get(&edge.node_from_id))
+                .map(|distance| distance + 1)
+                .max();
+
+            if let Some(distance) = distance {
+                distances.insert(node_id, distance);
+            }
+        }
+
+        self.output_node_ids()
+            .iter()
+            .filter_map(|id| distances.get(id))
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Get the node ids grouped into tiers by the edges between them rather
+    /// than by layer. Nodes without incoming edges are in the first tier, and
+    /// every other node is one tier after the latest node feeding into it, so
+    /// a node only depends on nodes in earlier tiers. Recurrent and disabled
+    /// edges are ignored. Ids within a tier are sorted.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// let layer_id = network.add_layer();
+    /// let hidden_node_id = Node::create(&mut network, layer_id, 0.0).unwrap();
+    ///
+    /// // the hidden node has no incoming edges, so it can be computed first
+    /// Edge::create(&mut network, input_node_id, output_node_id, 1.0).unwrap();
+    /// Edge::create(&mut network, hidden_node_id, output_node_id, 1.0).unwrap();
+    ///
+    /// assert_eq!(
+    ///     network.compute_order(),
+    ///     vec![vec![input_node_id, hidden_node_id], vec![output_node_id]]
+    /// );
+    /// ```
+    pub fn compute_order(&self) -> Vec<Vec<usize>> {
+        let mut tiers: HashMap<usize, usize> = HashMap::new();
+        let mut order: Vec<Vec<usize>> = Vec::new();
+
+        for node_id in self.topological_node_ids() {
+            let tier = self
+                .edges
+                .iter()
+                .filter(|edge| edge.node_to_id == node_id && !edge.recurrent && edge.enabled)
+                .filter_map(|edge| tiers.get(&edge.node_from_id))
+                .map(|tier| tier + 1)
+                .max()
+                .unwrap_or(0);
+
+            tiers.insert(node_id, tier);
+
+            if order.len() <= tier {
+                order.resize(tier + 1, Vec::new());
+            }
+
+            order[tier].push(node_id);
+        }
+
+        for tier in order.iter_mut() {
+            tier.sort();
+        }
+
+        order
+    }
+
+    /// Counts the trainable parameters of the network: one weight per edge
+    /// and one bias per hidden and output node. Input node biases are not
+    /// counted, since `fire` overwrites input values instead of adding to
+    /// them, and bias nodes always hold 1.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// let counts = network.parameter_count();
+    ///
+    /// assert_eq!(counts.weights, 2 * 3 + 3 * 1);
+    /// assert_eq!(counts.biases, 3 + 1);
+    /// assert_eq!(counts.total(), 13);
+    /// ```
+    pub fn parameter_count(&self) -> ParamCounts {
+        let biases = self
+            .nodes
+            .iter()
+            .filter(|node| matches!(node.node_type, NodeType::HiddenNode | NodeType::OutputNode))
+            .count();
+
+        ParamCounts {
+            weights: self.edges.len(),
+            biases,
+        }
+    }
+
+    /// Estimates the cost of one `fire`: one multiply-accumulate per enabled,
+    /// non-recurrent edge, plus one activation per hidden and output node.
+    /// Input activations are not counted, since inputs are linear unless
+    /// changed.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// assert_eq!(network.flops(), (2 * 3 + 3 * 1) + (3 + 1));
+    /// ```
+    pub fn flops(&self) -> usize {
+        let edges = self
+            .edges
+            .iter()
+            .filter(|edge| edge.enabled && !edge.recurrent)
+            .count();
+
+        edges + self.parameter_count().biases
+    }
+
+    /// Flattens the trainable parameters (see `parameter_count`) into a vec:
+    /// edge weights in order of edge id, then the biases of the hidden and
+    /// output nodes in order of node id. Use `import_parameters` to load them
+    /// back into a network with the same structure.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// let mut copy = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    ///
+    /// let parameters = network.export_parameters();
+    /// assert_eq!(parameters.len(), network.parameter_count().total());
+    ///
+    /// copy.import_parameters(&parameters).unwrap();
+    /// assert_eq!(copy.export_parameters(), parameters);
+    /// ```
+    pub fn export_parameters(&self) -> Vec<Float> {
+        let (edge_indexes, node_indexes) = self.parameter_indexes();
+
+        edge_indexes
+            .iter()
+            .map(|index| self.edges[*index].weight)
+            .chain(node_indexes.iter().map(|index| self.nodes[*index].bias))
+            .collect()
+    }
+
+    /// Loads parameters exported by `export_parameters`. Returns an error if
+    /// the number of parameters does not match `parameter_count`.
+    pub fn import_parameters(&mut self, parameters: &[Float]) -> Result<()> {
+        let (edge_indexes, node_indexes) = self.parameter_indexes();
+
+        ensure!(
+            parameters.len() == edge_indexes.len() + node_indexes.len(),
+            "Expected {} parameters, got {}",
+            edge_indexes.len() + node_indexes.len(),
+            parameters.len()
+        );
+
+        let (weights, biases) = parameters.split_at(edge_indexes.len());
+
+        for (index, weight) in edge_indexes.iter().zip(weights) {
+            self.edges[*index].weight = *weight;
+        }
+
+        for (index, bias) in node_indexes.iter().zip(biases) {
+            self.nodes[*index].bias = *bias;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces every edge weight with `f(weight)`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    ///
+    /// // round to one decimal place
+    /// network.map_weights(|weight| (weight * 10.0).round() / 10.0);
+    /// ```
+    pub fn map_weights(&mut self, mut f: impl FnMut(Float) -> Float) {
+        for edge in self.edges.iter_mut() {
+            edge.weight = f(edge.weight);
+        }
+    }
+
+    /// Replaces every node bias with `f(bias)`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// network.map_biases(|bias| bias + 0.1);
+    /// ```
+    pub fn map_biases(&mut self, mut f: impl FnMut(Float) -> Float) {
+        for node in self.nodes.iter_mut() {
+            node.bias = f(node.bias);
+        }
+    }
+
+    /// The sum of the absolute values of all edge weights, as used for L1
+    /// regularization.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(2, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_ids = network.input_node_ids();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_ids[0], output_node_id, 3.0).unwrap();
+    /// Edge::create(&mut network, input_node_ids[1], output_node_id, -4.0).unwrap();
+    ///
+    /// assert_eq!(network.l1_norm(), 7.0);
+    /// assert_eq!(network.l2_norm(), 25.0);
+    /// ```
+    pub fn l1_norm(&self) -> Float {
+        self.edges.iter().map(|edge| edge.weight.abs()).sum()
+    }
+
+    /// The sum of the squares of all edge weights, as used for L2
+    /// regularization. This is the squared Euclidean norm of the weights.
+    pub fn l2_norm(&self) -> Float {
+        self.edges.iter().map(|edge| edge.weight.powi(2)).sum()
+    }
+
+    /// Sets an element-wise transform that `fire` applies to the outputs:
+    /// `output[i] * scale[i] + offset[i]`. This rescales a network's outputs,
+    /// for example to undo target normalization, without changing its
+    /// weights. Both vectors must have one value per output node. The
+    /// transform is applied after the softmax of `create_classifier` and is
+    /// saved with the network.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, 1.0).unwrap();
+    ///
+    /// network.set_output_transform(vec![10.0], vec![5.0]).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.fire(vec![0.5], &mut output).unwrap();
+    /// assert_eq!(output, vec![10.0]);
+    /// ```
+    pub fn set_output_transform(&mut self, scale: Vec<Float>, offset: Vec<Float>) -> Result<()> {
+        ensure!(
+            scale.len() == self.output_size() && offset.len() == self.output_size(),
+            "Expected {} scales and offsets, got {} and {}",
+            self.output_size(),
+            scale.len(),
+            offset.len()
+        );
+
+        self.output_transform = Some((scale, offset));
+
+        Ok(())
+    }
+
+    /// Removes the transform set by `set_output_transform`.
+    pub fn clear_output_transform(&mut self) {
+        self.output_transform = None;
+    }
+
+    /// Clamps the value of every node into `min..=max` after the bias is
+    /// added and before the activation function runs. This keeps huge
+    /// weighted sums from saturating or overflowing the activation function.
+    /// By default there is no clamp. NaN values are not clamped.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, 100.0).unwrap();
+    ///
+    /// network.set_preactivation_clamp((-5.0, 5.0)).unwrap();
+    ///
+    /// let mut output = vec![];
+    /// network.fire(vec![1.0], &mut output).unwrap();
+    /// assert_eq!(output, vec![5.0]);
+    /// ```
+    pub fn set_preactivation_clamp(&mut self, range: (Float, Float)) -> Result<()> {
+        ensure!(
+            range.0 <= range.1,
+            "min must be a number no greater than max"
+        );
+
+        self.preactivation_clamp = Some(range);
+
+        Ok(())
+    }
+
+    /// Removes the clamp set by `set_preactivation_clamp`.
+    pub fn clear_preactivation_clamp(&mut self) {
+        self.preactivation_clamp = None;
+    }
+
+    /// In strict mode, every method that fires the network, such as `fire`,
+    /// `fire_checked`, `fire_current`, `fire_sequence` or `jacobian`, returns
+    /// an error if an output node cannot be reached from any input node,
+    /// instead of returning an output that ignores the inputs. Which outputs
+    /// are connected is worked out when the network's structure changes, not
+    /// on every fire.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Sigmoid).unwrap();
+    /// network.set_strict(true);
+    ///
+    /// let mut output = vec![];
+    /// assert!(network.fire(vec![0.5], &mut output).is_err());
+    ///
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, 1.0).unwrap();
+    /// network.fire(vec![0.5], &mut output).unwrap();
+    /// ```
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Whether the network is in strict mode. See `set_strict`.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Clamps every edge weight and node bias into `min..=max`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, activationfn::ActivationFn};
+    /// let mut network = Network::feedforward(&[2, 3, 1], ActivationFn::ReLU).unwrap();
+    /// network.import_parameters(&vec![5.0; 13]).unwrap();
+    ///
+    /// network.clamp_weights(-1.0, 1.0).unwrap();
+    /// assert!(network.export_parameters().iter().all(|parameter| *parameter == 1.0));
+    /// ```
+    pub fn clamp_weights(&mut self, min: Float, max: Float) -> Result<()> {
+        ensure!(min <= max, "min must be a number no greater than max");
+
+        for edge in self.edges.iter_mut() {
+            edge.weight = edge.weight.clamp(min, max);
+        }
+
+        for node in self.nodes.iter_mut() {
+            node.bias = node.bias.clamp(min, max);
+        }
+
+        Ok(())
+    }
+
+    /// Indexes of the edges and of the nodes with trainable biases, in the
+    /// order used by `export_parameters`.
+    fn parameter_indexes(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut edge_indexes = (0..self.edges.len()).collect::<Vec<usize>>();
+        edge_indexes.sort_by_key(|index| self.edges[*index].id);
+
+        let mut node_indexes = (0..self.nodes.len())
+            .filter(|index| {
+                matches!(
+                    self.nodes[*index].node_type,
+                    NodeType::HiddenNode | NodeType::OutputNode
+                )
+            })
+            .collect::<Vec<usize>>();
+        node_indexes.sort_by_key(|index| self.nodes[*index].id);
+
+        (edge_indexes, node_indexes)
+    }
+
+    /// Get a human-readable description of the network's architecture: the
+    /// number of nodes in each layer (in firing order), the total number of
+    /// edges and trainable parameters, the estimated cost of a forward pass
+    /// (see `flops`), and how many nodes use each activation function. Nodes
+    /// with a closure from `Node::set_activation_closure` are counted as
+    /// `Custom`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, 0.5).unwrap();
+    ///
+    /// println!("{}", network.summary());
+    /// ```
+    pub fn summary(&self) -> String {
+        let mut layers = self.layers.clone();
+        layers.sort();
+
+        let mut summary = String::new();
+        summary.push_str("Layers:\n");
+
+        for layer_id in layers {
+            let count = self
+                .nodes
+                .iter()
+                .filter(|node| node.layer_id == layer_id)
+                .count();
+
+            // writing to a String cannot fail
+            let _ = writeln!(summary, "  {:?}: {} nodes", layer_id, count);
+        }
+
+        let _ = writeln!(summary, "Edges: {}", self.edges.len());
+        let _ = writeln!(
+            summary,
+            "Trainable parameters: {}",
+            self.parameter_count().total()
+        );
+        let _ = writeln!(summary, "FLOPs per fire: {}", self.flops());
+
+        // there are only a few distinct activation functions, so count them
+        // in a list and format each one once; closures are counted as `None`
+        let mut counts: Vec<(Option<ActivationFn>, usize)> = vec![];
+
+        for node in self.nodes.iter() {
+            let activation_fn = match node.activation_closure {
+                Some(_) => None,
+                None => Some(node.activation_fn),
+            };
+
+            match counts.iter_mut().find(|(other, _)| *other == activation_fn) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((activation_fn, 1)),
+            }
+        }
+
+        let mut activations = counts
+            .into_iter()
+            .map(|(activation_fn, count)| match activation_fn {
+                Some(activation_fn) => (format!("{:?}", activation_fn), count),
+                None => ("Custom".to_string(), count),
+            })
+            .collect::<Vec<_>>();
+        activations.sort();
+
+        summary.push_str("Activations:\n");
+
+        for (activation_fn, count) in activations {
+            let _ = writeln!(summary, "  {}: {}", activation_fn, count);
+        }
+
+        summary
     }
 }
 
@@ -1,7 +1,12 @@
-use anyhow::{ensure, Result};
+use anyhow::{ensure, Context, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::{activationfn::ActivationFn, layer::LayerID, network::Network};
+use crate::{
+    activationfn::{ActivationFn, CustomActivation},
+    layer::LayerID,
+    network::Network,
+    Float,
+};
 
 /// Possible node types.
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
@@ -14,6 +19,9 @@ pub enum NodeType {
 
     /// An output node.
     OutputNode,
+
+    /// A bias node, whose value is always 1. See `Network::add_bias_node`.
+    BiasNode,
 }
 
 /// Nodes are the basic building blocks of a neural network.
@@ -22,13 +30,19 @@ pub struct Node {
     pub(crate) node_type: NodeType,
     pub(crate) id: usize,
     pub(crate) layer_id: LayerID,
-    pub(crate) value: f64,
-    pub(crate) bias: f64,
+    pub(crate) value: Float,
+    pub(crate) bias: Float,
     pub(crate) activation_fn: ActivationFn,
+    #[serde(default)]
+    pub(crate) frozen: bool,
+    #[serde(skip)]
+    pub(crate) activation_closure: Option<CustomActivation>,
 }
 
 impl Node {
-    /// Creates a new node.
+    /// Creates a new node, returning its id. Node ids are permanent: they
+    /// are issued in increasing order and never reused, even after a node is
+    /// removed.
     ///
     /// ### Examples
     /// ```
@@ -39,11 +53,13 @@ impl Node {
     /// let hidden_node_id = Node::create(&mut network, layerid, 0.0).unwrap();
     /// let output_node_id = network.output_node_ids().pop().unwrap();
     /// ```
-    pub fn create(network: &mut Network, layer_id: LayerID, bias: f64) -> Result<usize> {
-        Self::create_with_custom_activation(network, layer_id, bias, network.activation_fn)
+    pub fn create(network: &mut Network, layer_id: LayerID, bias: Float) -> Result<usize> {
+        let activation_fn = network.layer_activation_fn(layer_id);
+        Self::create_with_custom_activation(network, layer_id, bias, activation_fn)
     }
 
-    /// Creates a new node with a custom activation function.
+    /// Creates a new node with a custom activation function. Input nodes apply
+    /// their activation function to the value they are given when firing.
     ///
     /// ### Examples
     /// ```
@@ -54,10 +70,10 @@ impl Node {
     pub fn create_with_custom_activation(
         network: &mut Network,
         layer_id: LayerID,
-        bias: f64,
+        bias: Float,
         activation_fn: ActivationFn,
     ) -> Result<usize> {
-        let id = network.nodes.iter().map(|n| n.id).max().unwrap_or(0) + 1;
+        let id = network.new_node_id();
 
         ensure!(
             network.get_node(id).is_none(),
@@ -83,20 +99,98 @@ impl Node {
             value: 0.0,
             bias,
             activation_fn,
+            frozen: false,
+            activation_closure: None,
         };
 
         let id = node.id;
 
         network.nodes.push(node);
+        network.next_node_id = id + 1;
+        network.invalidate_cache();
 
         Ok(id)
     }
 
-    pub(crate) fn add_value(&mut self, value: f64) {
+    /// Replaces a node's activation function with a closure, for functions
+    /// that `ActivationFn` does not cover. The closure is differentiated
+    /// numerically wherever a derivative is needed. Closures cannot be
+    /// serialized, so serializing a network with a closure on any node
+    /// returns an error.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let output_node_id = network.output_node_ids()[0];
+    ///
+    /// Node::set_activation_closure(&mut network, output_node_id, |x| x * x).unwrap();
+    ///
+    /// assert!(network.serialize().is_err());
+    /// ```
+    pub fn set_activation_closure(
+        network: &mut Network,
+        node_id: usize,
+        activation_fn: impl Fn(Float) -> Float + Send + Sync + 'static,
+    ) -> Result<()> {
+        let node = network
+            .get_node_mut(node_id)
+            .with_context(|| format!("Node with id {} does not exist", node_id))?;
+
+        node.activation_closure = Some(CustomActivation::new(activation_fn));
+        network.invalidate_cache();
+
+        Ok(())
+    }
+
+    /// Removes a closure set with `Node::set_activation_closure`, so the node
+    /// uses its `ActivationFn` again.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, node::Node, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let output_node_id = network.output_node_ids()[0];
+    ///
+    /// Node::set_activation_closure(&mut network, output_node_id, |x| x * x).unwrap();
+    /// Node::clear_activation_closure(&mut network, output_node_id).unwrap();
+    ///
+    /// assert!(network.serialize().is_ok());
+    /// ```
+    pub fn clear_activation_closure(network: &mut Network, node_id: usize) -> Result<()> {
+        let node = network
+            .get_node_mut(node_id)
+            .with_context(|| format!("Node with id {} does not exist", node_id))?;
+
+        node.activation_closure = None;
+        network.invalidate_cache();
+
+        Ok(())
+    }
+
+    pub(crate) fn activate(&self, x: Float) -> Float {
+        match &self.activation_closure {
+            Some(activation_fn) => activation_fn.run(x),
+            None => self.activation_fn.run(x),
+        }
+    }
+
+    pub(crate) fn activation_derivative(&self, x: Float) -> Float {
+        match &self.activation_closure {
+            Some(activation_fn) => activation_fn.derivative(x),
+            None => self.activation_fn.derivative(x),
+        }
+    }
+
+    pub(crate) fn add_value(&mut self, value: Float) {
         self.value += value;
     }
 
     pub(crate) fn reset(&mut self) {
-        self.value = 0.0;
+        self.value = if self.node_type == NodeType::BiasNode {
+            1.0
+        } else {
+            0.0
+        };
     }
 }
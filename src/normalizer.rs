@@ -0,0 +1,143 @@
+use crate::Float;
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+
+/// How a `Normalizer` rescales each dimension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NormalizeMode {
+    /// Subtract the mean and divide by the standard deviation, giving a mean
+    /// of 0 and a standard deviation of 1.
+    Standard,
+
+    /// Subtract the minimum and divide by the range, mapping the fitted
+    /// values onto `0..=1`.
+    MinMax,
+}
+
+/// Per-dimension rescaling of inputs or targets. Fit it to a dataset, then
+/// `transform` values before using them and `inverse_transform` the
+/// network's outputs to get back to the original scale.
+///
+/// A dimension that is the same in every sample would divide by 0, so it is
+/// only shifted, not scaled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Normalizer {
+    mode: NormalizeMode,
+    offsets: Vec<Float>,
+    scales: Vec<Float>,
+}
+
+impl Normalizer {
+    /// Fits a normalizer to samples that all have the same length.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::normalizer::{Normalizer, NormalizeMode};
+    /// let targets = vec![vec![10.0, 0.0], vec![20.0, 1.0], vec![30.0, 2.0]];
+    /// let normalizer = Normalizer::fit(&targets, NormalizeMode::MinMax).unwrap();
+    ///
+    /// assert_eq!(normalizer.transform(&[20.0, 2.0]).unwrap(), vec![0.5, 1.0]);
+    /// assert_eq!(normalizer.inverse_transform(&[0.5, 1.0]).unwrap(), vec![20.0, 2.0]);
+    ///
+    /// // normalizers serialize, so they can be saved alongside a network
+    /// let json = serde_json::to_string(&normalizer).unwrap();
+    /// let loaded: Normalizer = serde_json::from_str(&json).unwrap();
+    /// assert_eq!(loaded, normalizer);
+    /// ```
+    pub fn fit(samples: &[Vec<Float>], mode: NormalizeMode) -> Result<Self> {
+        ensure!(
+            !samples.is_empty(),
+            "Cannot fit a normalizer without samples"
+        );
+
+        let size = samples[0].len();
+
+        for (index, sample) in samples.iter().enumerate() {
+            ensure!(
+                sample.len() == size,
+                "Sample {} has {} values, expected {}",
+                index,
+                sample.len(),
+                size
+            );
+        }
+
+        let count = samples.len() as Float;
+        let column = |dimension: usize| samples.iter().map(move |sample| sample[dimension]);
+
+        let (offsets, scales) = (0..size)
+            .map(|dimension| match mode {
+                NormalizeMode::Standard => {
+                    let mean = column(dimension).sum::<Float>() / count;
+                    let variance = column(dimension)
+                        .map(|value| (value - mean).powi(2))
+                        .sum::<Float>()
+                        / count;
+
+                    (mean, variance.sqrt())
+                }
+                NormalizeMode::MinMax => {
+                    let min = column(dimension).fold(Float::INFINITY, Float::min);
+                    let max = column(dimension).fold(Float::NEG_INFINITY, Float::max);
+
+                    (min, max - min)
+                }
+            })
+            .map(|(offset, scale)| (offset, if scale > 0.0 { scale } else { 1.0 }))
+            .unzip();
+
+        Ok(Self {
+            mode,
+            offsets,
+            scales,
+        })
+    }
+
+    /// The mode the normalizer was fitted with.
+    pub fn mode(&self) -> NormalizeMode {
+        self.mode
+    }
+
+    /// The number of values in each sample.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Whether the normalizer was fitted to empty samples.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Rescales a sample.
+    pub fn transform(&self, values: &[Float]) -> Result<Vec<Float>> {
+        self.check_len(values)?;
+
+        Ok(values
+            .iter()
+            .zip(self.offsets.iter().zip(self.scales.iter()))
+            .map(|(value, (offset, scale))| (value - offset) / scale)
+            .collect())
+    }
+
+    /// Undoes `transform`, returning a sample to its original scale.
+    pub fn inverse_transform(&self, values: &[Float]) -> Result<Vec<Float>> {
+        self.check_len(values)?;
+
+        Ok(values
+            .iter()
+            .zip(self.offsets.iter().zip(self.scales.iter()))
+            .map(|(value, (offset, scale))| value * scale + offset)
+            .collect())
+    }
+
+    fn check_len(&self, values: &[Float]) -> Result<()> {
+        ensure!(
+            values.len() == self.len(),
+            "Expected {} values, got {}",
+            self.len(),
+            values.len()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crate::{
+    activationfn::{ActivationFn, CustomActivation},
+    network::Network,
+    node::NodeType,
+    Float,
+};
+use anyhow::{ensure, Context, Result};
+
+#[derive(Clone, Debug)]
+struct QuantizedNode {
+    node_type: NodeType,
+    bias: i64,
+    activation_fn: ActivationFn,
+    activation_closure: Option<CustomActivation>,
+    incoming: Vec<(usize, i64)>,
+}
+
+/// A fixed-point copy of a network, for inference where floating point math
+/// is slow or unavailable.
+///
+/// Weights, biases, inputs and outputs are integers equal to the real value
+/// multiplied by `scale`. Weighted sums are computed with integer arithmetic
+/// and rescaled back to `scale` before the bias is added. `ReLU`, `Linear` and
+/// `Step` are evaluated on integers; other activation functions, including
+/// closures set with `Node::set_activation_closure`, are evaluated by
+/// converting the value back to a float, so they do not benefit from integer
+/// speed.
+///
+/// A larger scale keeps more precision, but weights and biases are saturated
+/// to the range of an `i32`, so `scale` times the largest parameter must fit.
+/// Each layer rounds its values to the nearest `1 / scale`, so the error grows
+/// with the depth of the network.
+#[derive(Clone, Debug)]
+pub struct QuantizedNetwork {
+    scale: i64,
+    clamp: Option<(i64, i64)>,
+    nodes: Vec<QuantizedNode>,
+    input_indexes: Vec<usize>,
+    output_indexes: Vec<usize>,
+}
+
+impl QuantizedNetwork {
+    pub(crate) fn new(network: &Network, scale: Float) -> Result<Self> {
+        ensure!(
+            scale >= 1.0 && scale.fract() == 0.0 && scale <= i32::MAX as Float,
+            "Scale must be a whole number between 1 and {}",
+            i32::MAX
+        );
+
+        let quantize = |x: Float| (x * scale).round() as i32 as i64;
+
+        // sorting by layer gives an order in which every edge points forwards
+        let mut nodes = network.nodes.iter().collect::<Vec<_>>();
+        nodes.sort_by_key(|node| (node.layer_id, node.id));
+
+        let indexes = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect::<HashMap<usize, usize>>();
+
+        let mut quantized_nodes = nodes
+            .iter()
+            .map(|node| QuantizedNode {
+                node_type: node.node_type,
+                bias: quantize(node.bias),
+                activation_fn: node.activation_fn,
+                activation_closure: node.activation_closure.clone(),
+                incoming: vec![],
+            })
+            .collect::<Vec<QuantizedNode>>();
+
+        for edge in network
+            .edges
+            .iter()
+            .filter(|edge| !edge.recurrent && edge.enabled)
+        {
+            let node_from_index = *indexes
+                .get(&edge.node_from_id)
+                .context("Node from does not exist")?;
+            let node_to_index = *indexes
+                .get(&edge.node_to_id)
+                .context("Node to does not exist")?;
+
+            quantized_nodes[node_to_index]
+                .incoming
+                .push((node_from_index, quantize(edge.weight)));
+        }
+
+        let type_indexes = |node_type: NodeType| {
+            let mut ids = nodes
+                .iter()
+                .filter(|node| node.node_type == node_type)
+                .map(|node| node.id)
+                .collect::<Vec<usize>>();
+            ids.sort();
+            ids.iter().map(|id| indexes[id]).collect::<Vec<usize>>()
+        };
+
+        Ok(Self {
+            scale: scale as i64,
+            clamp: network
+                .preactivation_clamp
+                .map(|(min, max)| (quantize(min), quantize(max))),
+            input_indexes: type_indexes(NodeType::InputNode),
+            output_indexes: type_indexes(NodeType::OutputNode),
+            nodes: quantized_nodes,
+        })
+    }
+
+    /// Runs the inputs of the network. Inputs and outputs are real values
+    /// multiplied by the scale the network was quantized with, in the same
+    /// order as `Network::fire`.
+    ///
+    /// ### Example
+    /// ```
+    /// # use nnrs::{network::Network, edge::Edge, activationfn::ActivationFn};
+    /// let mut network = Network::create(1, 1, ActivationFn::Linear).unwrap();
+    /// let input_node_id = network.input_node_ids().pop().unwrap();
+    /// let output_node_id = network.output_node_ids().pop().unwrap();
+    /// Edge::create(&mut network, input_node_id, output_node_id, 0.5).unwrap();
+    ///
+    /// let quantized = network.quantize(1000.0).unwrap();
+    ///
+    /// // 0.8 * 0.5 = 0.4
+    /// assert_eq!(quantized.fire(&[800]).unwrap(), vec![400]);
+    /// ```
+    pub fn fire(&self, inputs: &[i32]) -> Result<Vec<i32>> {
+        ensure!(
+            self.input_indexes.len() == inputs.len(),
+            "Number of inputs does not match number of input nodes"
+        );
+
+        let mut values = vec![0i64; self.nodes.len()];
+
+        for (index, input) in self.input_indexes.iter().zip(inputs) {
+            values[*index] = self.activate(&self.nodes[*index], *input as i64);
+        }
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            match node.node_type {
+                NodeType::InputNode => continue,
+                NodeType::BiasNode => {
+                    values[index] = self.scale;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let sum = node
+                .incoming
+                .iter()
+                .map(|(from, weight)| values[*from].saturating_mul(*weight))
+                .fold(0i64, |acc, x| acc.saturating_add(x));
+
+            let mut value = self.rescale(sum).saturating_add(node.bias);
+
+            if let Some((min, max)) = self.clamp {
+                value = value.clamp(min, max);
+            }
+
+            values[index] = self.activate(node, value);
+        }
+
+        Ok(self
+            .output_indexes
+            .iter()
+            .map(|index| values[*index].clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+            .collect())
+    }
+
+    /// Divides by the scale, rounding to the nearest integer.
+    fn rescale(&self, value: i64) -> i64 {
+        let half = self.scale / 2;
+
+        if value >= 0 {
+            value.saturating_add(half) / self.scale
+        } else {
+            value.saturating_sub(half) / self.scale
+        }
+    }
+
+    fn activate(&self, node: &QuantizedNode, value: i64) -> i64 {
+        let scale = self.scale as Float;
+
+        if let Some(activation_fn) = &node.activation_closure {
+            return (activation_fn.run(value as Float / scale) * scale).round() as i64;
+        }
+
+        match node.activation_fn {
+            ActivationFn::ReLU => value.max(0),
+            ActivationFn::Linear => value,
+            ActivationFn::Step(threshold) => {
+                if value > (threshold * scale).round() as i64 {
+                    self.scale
+                } else {
+                    0
+                }
+            }
+            activation_fn => (activation_fn.run(value as Float / scale) * scale).round() as i64,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::Float;
+use rand::Rng;
+
+/// Strategy for picking the weights of newly created edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightInit {
+    /// Every weight is set to the given value.
+    Constant(Float),
+
+    /// Weights are drawn uniformly from `-limit..=limit`.
+    Uniform(Float),
+
+    /// Glorot/Xavier uniform: `limit = sqrt(6 / (fan_in + fan_out))`.
+    /// Suited to Sigmoid and Tanh layers.
+    Xavier,
+
+    /// He/Kaiming uniform: `limit = sqrt(6 / fan_in)`. Suited to ReLU layers.
+    He,
+}
+
+impl WeightInit {
+    pub(crate) fn sample(&self, rng: &mut impl Rng, fan_in: usize, fan_out: usize) -> Float {
+        let limit = match self {
+            WeightInit::Constant(weight) => return *weight,
+            WeightInit::Uniform(limit) => *limit,
+            WeightInit::Xavier => (6.0 / (fan_in + fan_out).max(1) as Float).sqrt(),
+            WeightInit::He => (6.0 / fan_in.max(1) as Float).sqrt(),
+        };
+
+        if limit > 0.0 {
+            rng.gen_range(-limit..=limit)
+        } else {
+            0.0
+        }
+    }
+}